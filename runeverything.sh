@@ -5,7 +5,7 @@ mkdir "results"
 
 
 # Specify the path to your Rust executable
-RUST_EXECUTABLE="cargo run --release"
+RUST_EXECUTABLE="cargo run --release --"
 
 # Directory containing the .gph files
 INSTANCES_DIR="dense_instances"
@@ -22,7 +22,7 @@ for file in "$INSTANCES_DIR"/*.gph; do
         filename="${file##*/}"  # Extract filename without path
         filename_without_extension="${filename%.gph}"  # Remove .gph extension
         echo "Running $filename..."
-        $RUST_EXECUTABLE "$file" "${filename_without_extension}.txt"
+        $RUST_EXECUTABLE all "$file" --output "results/${filename_without_extension}.csv"
     fi
 done
 
@@ -41,7 +41,7 @@ for file in "$INSTANCES_DIR"/*.gph; do
         filename="${file##*/}"  # Extract filename without path
         filename_without_extension="${filename%.gph}"  # Remove .gph extension
         echo "Running $filename..."
-        $RUST_EXECUTABLE "$file" "${filename_without_extension}.txt"
+        $RUST_EXECUTABLE all "$file" --output "results/${filename_without_extension}.csv"
     fi
 done
 
@@ -1,27 +1,53 @@
+import csv
 import os
 import matplotlib.pyplot as plt
 from scipy.optimize import curve_fit
 import numpy as np
 
+# The methods in the order of the plots, by the algorithm column of the bench records
+METHODS = {
+    'BaseLineFloydWarshall': 1,
+    'FloydWarshall': 2,
+    'Dijkstra': 3,
+    'DijkstraPar': 4,
+    'FloydWarshallBlock': 5,
+    'FloydWarshallBlockPar': 6,
+}
+
+## Read the CSV records of `apsp all --output`, one row per algorithm with the number of nodes and
+## the mean and standard deviation of the runtime. If the file was appended to by several runs the
+## last record of an algorithm is used
 def read_file(file_path):
+    data = {}
+    with open(file_path, 'r', newline='') as file:
+        for row in csv.DictReader(file):
+            if row['algorithm'] in METHODS:
+                data[METHODS[row['algorithm']]] = (int(row['nodes']), float(row['mean']), float(row['std']))
+    return data
+
+## Read the results of the report, which have one line "mean std" per method in the order above
+def read_txt_file(file_path):
     with open(file_path, 'r') as file:
         lines = file.readlines()
         n_value = int(os.path.basename(file_path).split('_')[1].split('.')[0])
         data = [(n_value, entry[0], entry[1]) for entry in [tuple(map(float, line.split())) for line in lines]]
-    return data
+    return {i + 1: entry for i, entry in enumerate(data)}
 
 def read_all_files(folder_path):
     all_data = {i + 1: [] for i in range(6)}
 
-    files = [f for f in os.listdir(folder_path) if f.startswith('dense_') and f.endswith('.txt')]
+    # A .txt file of the report is only read if there is no .csv file of the same instance
+    names = os.listdir(folder_path)
+    files = [f for f in names if f.startswith('dense_') and (f.endswith('.csv') or
+             (f.endswith('.txt') and f[:-len('.txt')] + '.csv' not in names))]
 
     for file_name in files:
         try:
             file_path = os.path.join(folder_path, file_name)
-            data = read_file(file_path)
+            data = read_file(file_path) if file_name.endswith('.csv') else read_txt_file(file_path)
 
-            for i, entry in enumerate(data):
-                all_data[i + 1].append(entry)
+            for i, entry in data.items():
+                all_data[i].append(entry)
         except Exception as e:
             print(f"Error processing file {file_name}: {e}")
 
@@ -3,9 +3,8 @@
 script_dir="$(cd "$(dirname "$0")" && pwd)"
 project_dir="$(cd $script_dir/.. && pwd)"
 
-source $script_dir/setup_venv.sh
-
-setup_venv
+# The generate command of the Rust executable, the same seed gives the same instances
+GENERATE="cargo run --release --manifest-path $project_dir/Cargo.toml -- generate"
 
 # mkdir "sparse_instances"
 # for num_nodes in 100 200 300 400 500 600 700 800 900 1000 1100 1200 1300 1400 1500 2000 2500 3000 3500 4000 4500 5000 6000 7000 8000
@@ -13,10 +12,8 @@ setup_venv
 #     probability=$(echo "scale=10; 1/$num_nodes" | bc)  # Adjust the scale as needed
 #     output_filename="sparse_instances/sparse_${num_nodes}.gph"
 
-#     # Run the Python script with command-line arguments
-#     python3 generate_graph.py $num_nodes $probability $output_filename
-
-#     echo "Generated: $output_filename"
+#     # Generate the graph with the seeded generator of the Rust executable
+#     $GENERATE gnp --nodes $num_nodes --probability $probability $output_filename
 
 # done
 # rm sparse_instances.zip
@@ -27,17 +24,13 @@ setup_venv
 mkdir "$project_dir/dense_instances"
 for num_nodes in 100 200 300 400 500 600 700 800 900 1000 1100 1200 1300 1400 1500 2000 2500 3000 
 do
-    probability=1  # Adjust the scale as needed
     output_filename="$project_dir/dense_instances/dense_${num_nodes}.gph"
 
-    # Run the Python script with command-line arguments
-    python3 $script_dir/generate_graph.py $num_nodes $probability $output_filename
-
-    echo "Generated: $output_filename"
+    # Every pair of nodes is connected
+    $GENERATE complete --nodes $num_nodes $output_filename
 
 done
 
 # rm dense_instances.zip
 # zip -r dense_instances.zip dense_instances
-# rm -r dense_instances
-deactivate
\ No newline at end of file
+# rm -r dense_instances
\ No newline at end of file
@@ -2,5 +2,140 @@ pub mod example;
 pub mod floyd_warshall_am;
 pub mod dijkstra_am;
 pub mod dijkstra_par;
+pub mod dijkstra_csr;
 pub mod floyd_warshall_block;
-pub mod floyd_warshall_block_par;
\ No newline at end of file
+pub mod floyd_warshall_block_par;
+pub mod bellman_ford;
+pub mod johnson;
+#[cfg(feature = "rayon")]
+pub mod dijkstra_rayon;
+#[cfg(feature = "rayon")]
+pub mod floyd_warshall_rayon;
+#[cfg(feature = "rayon")]
+pub mod floyd_warshall_block_rayon;
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::algorithms::dijkstra_am::Dijkstra;
+    use crate::algorithms::dijkstra_csr::DijkstraCSR;
+    use crate::algorithms::dijkstra_par::DijkstraPar;
+    use crate::algorithms::floyd_warshall_am::FloydWarshall;
+    use crate::algorithms::floyd_warshall_block::FloydWarshallBlock;
+    use crate::algorithms::floyd_warshall_block_par::FloydWarshallBlockPar;
+    use crate::algorithms::johnson::Johnson;
+    use crate::base::{APSPAlgorithm, ExecuteError};
+    use crate::loader::LoadOptions;
+    use crate::testing::TempFile;
+
+    // The graphs in the sparse format with zero-based ids, the edges are parsed again by the test
+    // to check the paths. Parallel edges and zero weights give several shortest paths, of which
+    // the algorithms have to pick one that really exists
+    const GRAPHS: [(&str, &str); 4] = [
+        (
+            "mixed",
+            "7 10 directed zero-based\n0 1 4\n0 2 1\n2 1 2\n1 3 5\n2 3 8\n3 4 3\n4 1 1\n\
+             0 1 3\n5 6 2\n6 5 2\n",
+        ),
+        (
+            "zero weights",
+            "6 9 directed zero-based\n0 1 0\n1 0 0\n1 2 0\n0 2 0\n2 3 1\n1 3 1\n3 4 0\n\
+             4 3 0\n4 5 0\n",
+        ),
+        (
+            "undirected zero weights",
+            "5 6 undirected zero-based\n0 1 0\n1 2 0\n0 2 0\n2 3 2\n3 4 0\n1 4 2\n",
+        ),
+        (
+            "negative",
+            "5 7 directed zero-based\n0 1 2\n1 2 -1\n0 2 3\n2 3 -2\n3 4 0\n1 4 -3\n4 2 3\n",
+        ),
+    ];
+
+    // All algorithms which can reconstruct the paths
+    fn algorithms() -> Vec<(&'static str, Box<dyn APSPAlgorithm<i32>>)> {
+        #[allow(unused_mut)]
+        let mut algorithms: Vec<(&'static str, Box<dyn APSPAlgorithm<i32>>)> = vec![
+            ("FloydWarshall", Box::new(FloydWarshall::new().with_paths())),
+            ("Dijkstra", Box::new(Dijkstra::new().with_paths())),
+            ("DijkstraPar", Box::new(DijkstraPar::new(3).with_paths())),
+            ("DijkstraCSR", Box::new(DijkstraCSR::new().with_paths())),
+            ("FloydWarshallBlock", Box::new(FloydWarshallBlock::new(3).with_paths())),
+            ("FloydWarshallBlockPar", Box::new(FloydWarshallBlockPar::new(3, 2).with_paths())),
+            ("Johnson", Box::new(Johnson::new().with_paths())),
+        ];
+        #[cfg(feature = "rayon")]
+        {
+            use crate::algorithms::dijkstra_rayon::DijkstraRayon;
+            use crate::algorithms::floyd_warshall_block_rayon::FloydWarshallBlockRayon;
+            use crate::algorithms::floyd_warshall_rayon::FloydWarshallRayon;
+            use crate::pool::Pool;
+
+            let pool = Pool::new(3).unwrap();
+            algorithms.push(("DijkstraRayon", Box::new(DijkstraRayon::new(&pool).with_paths())));
+            algorithms.push((
+                "FloydWarshallRayon",
+                Box::new(FloydWarshallRayon::new(&pool).with_paths()),
+            ));
+            algorithms.push((
+                "FloydWarshallBlockRayon",
+                Box::new(FloydWarshallBlockRayon::new(&pool, 2).with_paths()),
+            ));
+        }
+        algorithms
+    }
+
+    // The lightest edge between each pair of nodes
+    fn lightest_edges(contents: &str) -> HashMap<(usize, usize), i32> {
+        let mut lines = contents.lines();
+        let directed = lines.next().unwrap().contains(" directed");
+        let mut edges = HashMap::new();
+        for line in lines {
+            let values: Vec<i32> = line.split(' ').map(|value| value.parse().unwrap()).collect();
+            let (from, to) = (values[0] as usize, values[1] as usize);
+            let mut pairs = vec![(from, to)];
+            if !directed {
+                pairs.push((to, from));
+            }
+            for pair in pairs {
+                let weight = edges.entry(pair).or_insert(values[2]);
+                *weight = (*weight).min(values[2]);
+            }
+        }
+        edges
+    }
+
+    #[test]
+    fn paths_follow_the_edges_and_add_up_to_the_distance() {
+        for (graph, contents) in GRAPHS {
+            let file = TempFile::with_contents("paths.gph", contents);
+            let edges = lightest_edges(contents);
+            for (name, mut algorithm) in algorithms() {
+                algorithm.load_graph_with(&file.path, true, &LoadOptions::new()).unwrap();
+                match algorithm.execute() {
+                    // Dijkstra refuses the negative weights
+                    Err(ExecuteError::NegativeEdge { .. }) if graph == "negative" => continue,
+                    execution => execution.unwrap(),
+                }
+                let result = algorithm.get_result();
+                let n = result.node_count();
+                for (from, to) in (0..n).flat_map(|from| (0..n).map(move |to| (from, to))) {
+                    let context = format!("{} on the {} graph, {} -> {}", name, graph, from, to);
+                    let Some(distance) = result.get(from, to) else {
+                        assert_eq!(result.path(from, to), None, "{}", context);
+                        continue;
+                    };
+                    let path = result.path(from, to).expect(&context);
+                    assert_eq!(path.first(), Some(&from), "{}", context);
+                    assert_eq!(path.last(), Some(&to), "{}", context);
+                    let weight: i32 = path
+                        .windows(2)
+                        .map(|edge| *edges.get(&(edge[0], edge[1])).expect(&context))
+                        .sum();
+                    assert_eq!(weight, distance, "{}: {:?}", context, path);
+                }
+            }
+        }
+    }
+}
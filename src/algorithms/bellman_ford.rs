@@ -0,0 +1,99 @@
+// This is our implementation of the Bellman-Ford algorithm
+// It is not used as an APSP algorithm on its own, but to compute the potentials for Johnson's
+// algorithm and to find the nodes on a negative cycle when one of the FW variants detects one
+// 30-01-2024
+
+// Import crates from our files
+use crate::base::ExecuteError;
+use crate::graph::{GraphAM, GraphCSR};
+use crate::weight::Weight;
+
+/// Bellman-Ford from a virtual source which has an edge of weight zero to every node.
+/// Returns the distance from the virtual source to every node (the potentials for Johnson's
+/// algorithm), these are always zero or negative. Returns the cycle if there is a negative cycle.
+/// A round relaxes the edges of the CSR graph, so it takes O(nm) instead of O(n^3)
+pub fn bellman_ford<W: Weight>(graph: &GraphCSR<W>) -> Result<Vec<W>, ExecuteError> {
+    let n = graph.node_count;
+
+    // The virtual source gives all nodes a distance of zero to start with
+    let mut potential: Vec<W> = vec![W::zero(); n];
+    let mut predecessor: Vec<Option<usize>> = vec![None; n];
+
+    // With the virtual source there are n + 1 nodes, so after n rounds all distances are final.
+    // If the distances still change in round n + 1 there is a negative cycle
+    for round in 0..=n {
+        let mut last_relaxed = None;
+
+        // Relax all edges
+        for from in 0..n {
+            for (to, weight) in graph.neighbours(from) {
+                let new_distance = potential[from].add_weight(weight);
+                if new_distance < potential[to] {
+                    potential[to] = new_distance;
+                    predecessor[to] = Some(from);
+                    last_relaxed = Some(to);
+                }
+            }
+        }
+
+        match last_relaxed {
+            // Nothing changed so the distances are final
+            None => break,
+            // Still changing in the last round, so there is a negative cycle
+            Some(node) if round == n => {
+                return Err(ExecuteError::NegativeCycle(trace_cycle(&predecessor, node)))
+            }
+            Some(_) => {}
+        }
+    }
+
+    // A potential clamped to the minimum stops changing, so a negative cycle can end the rounds
+    // early. Otherwise the distance from the start of the path does not fit in the weight type
+    if let Some(node) = potential.iter().position(|value| value.is_saturated()) {
+        let cycle = trace_cycle(&predecessor, node);
+        if !cycle.is_empty() {
+            return Err(ExecuteError::NegativeCycle(cycle));
+        }
+        let mut from = node;
+        while let Some(previous) = predecessor[from] {
+            from = previous;
+        }
+        return Err(ExecuteError::Overflow { from, to: node });
+    }
+    Ok(potential)
+}
+
+/// Used by the FW variants once they found a negative distance on the diagonal. The distances
+/// of the FW variants do not tell which nodes are on the cycle, so search it with Bellman-Ford
+pub fn negative_cycle_error<W: Weight>(graph: &GraphAM<W>) -> ExecuteError {
+    match bellman_ford(&GraphCSR::from(graph)) {
+        Err(error) => error,
+        Ok(_) => ExecuteError::NegativeCycle(Vec::new()),
+    }
+}
+
+// Walk back from a node that was relaxed in the last round. After n steps we are sure to be on
+// the cycle, from there walk back once more until we are at the same node again
+fn trace_cycle(predecessor: &[Option<usize>], start: usize) -> Vec<usize> {
+    let mut node = start;
+    for _ in 0..predecessor.len() {
+        match predecessor[node] {
+            Some(previous) => node = previous,
+            None => return Vec::new(),
+        }
+    }
+
+    let mut cycle = vec![node];
+    let mut current = node;
+    while let Some(previous) = predecessor[current] {
+        if previous == node {
+            break;
+        }
+        cycle.push(previous);
+        current = previous;
+    }
+
+    // We walked the cycle backwards, so reverse to get the order of the edges
+    cycle.reverse();
+    cycle
+}
@@ -0,0 +1,237 @@
+// This is our implemenatation of the Dijkstra algorithm on a graph in CSR format
+// for the all pair shortest path of a graph. Only the real out-edges of a node are relaxed,
+// so every start node costs O(m log n) instead of O(n^2) for the adjacency matrix
+// 30-01-2024
+
+// Import crates
+use std::cmp::PartialOrd;
+use std::cmp::{Eq, Ord, Ordering};
+use std::collections::BinaryHeap;
+
+// Import crates from our files
+use crate::base::{
+    load_graph_into, APSPAlgorithm, APSPResult, AlgorithmConfig, DistanceMatrix, ExecuteError,
+    PredecessorMatrix,
+};
+use crate::graph::GraphCSR;
+use crate::labels::Labels;
+use crate::loader::{GraphFormat, LoadError, LoadOptions};
+use crate::matrix::Matrix;
+use crate::query::{dijkstra_query, DistanceTable, Query, ShortestPathQuery};
+use crate::weight::Weight;
+
+// Struct which is used for the BinaryHeap. Since we use traits for our implementation
+// it is necessary to parse in a struct like this.
+// The struct is adapted from the petgraph crate.
+#[derive(Copy, Clone, Debug)]
+struct HeapElements<Weight, Node>(Weight, Node);
+
+impl<Weight: PartialOrd, Node> PartialEq for HeapElements<Weight, Node> {
+    #[inline]
+    fn eq(&self, other: &HeapElements<Weight, Node>) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<Weight: PartialOrd, Node> Eq for HeapElements<Weight, Node> {}
+
+impl<Weight: PartialOrd, Node> PartialOrd for HeapElements<Weight, Node> {
+    #[inline]
+    fn partial_cmp(&self, other: &HeapElements<Weight, Node>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<Weight: PartialOrd, Node> Ord for HeapElements<Weight, Node> {
+    #[inline]
+    fn cmp(&self, other: &HeapElements<Weight, Node>) -> Ordering {
+        let a = &self.0;
+        let b = &other.0;
+        if a == b {
+            Ordering::Equal
+        } else if a < b {
+            Ordering::Greater
+        } else if a > b {
+            Ordering::Less
+        } else if a.ne(a) && b.ne(b) {
+            // these are the NaN cases
+            Ordering::Equal
+        } else if a.ne(a) {
+            // Order NaN less, so that it is last in the MinScore order
+            Ordering::Less
+        } else {
+            Ordering::Greater
+        }
+    }
+}
+
+/// Dijkstra from a single start node. The distance and predecessor slices must have a length of
+/// node count and are expected to be filled with INFINITY and None
+pub fn dijkstra_single_source<W: Weight>(
+    graph: &GraphCSR<W>,
+    start_node: usize,
+    distance: &mut [W],
+    predecessor: &mut [Option<usize>],
+) {
+    dijkstra_search(graph, start_node, distance, predecessor, |_| false);
+}
+
+/// Same as [`dijkstra_single_source`], but settled is called for every node once its distance is
+/// final and the search stops as soon as it returns true. The distances of the nodes which are
+/// not settled by then are upper bounds
+pub fn dijkstra_search<W: Weight>(
+    graph: &GraphCSR<W>,
+    start_node: usize,
+    distance: &mut [W],
+    predecessor: &mut [Option<usize>],
+    mut settled: impl FnMut(usize) -> bool,
+) {
+    // Set the distance of the start node to 0
+    distance[start_node] = W::zero();
+
+    // Priority queue to keep track of nodes and their distances and push first value
+    let mut priority_queue = BinaryHeap::new();
+    priority_queue.push(HeapElements(W::zero(), start_node));
+
+    // Continue running until the priority queue is empty
+    while let Some(HeapElements(current_weight, current_node)) = priority_queue.pop() {
+        // Skip the element if a shorter distance to this node was found after it was pushed
+        if current_weight > distance[current_node] {
+            continue;
+        }
+        if settled(current_node) {
+            break;
+        }
+
+        // Loop over the out-edges of the current node and update distance + priority queue
+        for (neighbour, w) in graph.neighbours(current_node) {
+            let new_distance = current_weight.add_weight(w);
+            if new_distance < distance[neighbour] {
+                distance[neighbour] = new_distance;
+                predecessor[neighbour] = Some(current_node);
+                priority_queue.push(HeapElements(new_distance, neighbour));
+            }
+        }
+    }
+}
+
+/// Dijkstra from the start nodes first_node.. into the rows of the distance and predecessor
+/// matrices, row r belongs to start node first_node + r. The predecessor matrix can be left
+/// empty if the paths are not needed
+pub fn dijkstra_rows<W: Weight>(
+    graph: &GraphCSR<W>,
+    first_node: usize,
+    distance: &mut DistanceMatrix<W>,
+    predecessors: &mut PredecessorMatrix,
+) {
+    let track_paths = predecessors.rows() > 0;
+
+    // Scratch row for the predecessors if they are not kept
+    let mut scratch: Vec<Option<usize>> = Vec::new();
+
+    for (r, distance_row) in distance.iter_rows_mut().enumerate() {
+        let predecessor_row = if track_paths {
+            predecessors.row_mut(r)
+        } else {
+            scratch.clear();
+            scratch.resize(graph.node_count, None);
+            &mut scratch[..]
+        };
+        dijkstra_single_source(graph, first_node + r, distance_row, predecessor_row);
+    }
+}
+
+/// Dijkstra Algorithm for all start nodes
+/// If track_paths is true the predecessor matrix is computed as well, otherwise it is left empty
+pub fn dijkstra_csr<W: Weight>(
+    graph: &GraphCSR<W>,
+    track_paths: bool,
+) -> (DistanceMatrix<W>, PredecessorMatrix) {
+    let n = graph.node_count;
+    let mut distance_matrix = Matrix::new(n, n, W::INFINITY);
+    let mut predecessor_matrix = if track_paths {
+        Matrix::new(n, n, None)
+    } else {
+        Matrix::default()
+    };
+
+    dijkstra_rows(graph, 0, &mut distance_matrix, &mut predecessor_matrix);
+
+    // Return the distance matrix and the predecessors
+    (distance_matrix, predecessor_matrix)
+}
+
+/// Struct for Dijkstra algorithm on a graph in CSR format
+pub struct DijkstraCSR<W>
+where
+    W: Weight,
+{
+    /// Struct containing the Graph and the Shortest Path
+    pub graph: GraphCSR<W>,
+    pub shortest_paths: DistanceMatrix<W>,
+    /// Predecessor matrix, only filled if the paths are tracked
+    pub predecessors: PredecessorMatrix,
+    /// Whether the paths are tracked and the overflow is checked
+    pub config: AlgorithmConfig,
+}
+
+// Implementation to compute a new struct
+impl<W: Weight> Default for DijkstraCSR<W> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<W: Weight> DijkstraCSR<W> {
+    pub fn new() -> Self {
+        Self {
+            graph: GraphCSR::new(),
+            shortest_paths: Matrix::default(),
+            predecessors: Matrix::default(),
+            config: AlgorithmConfig::new(),
+        }
+    }
+}
+
+// Implementation for the APSPAlgorithm struct
+impl<W: Weight> APSPAlgorithm<W> for DijkstraCSR<W> {
+    // Load the graph
+    fn load_graph_from(
+        &mut self,
+        file_path: &str,
+        format: GraphFormat,
+        options: &LoadOptions,
+    ) -> Result<Option<Labels>, LoadError> {
+        load_graph_into(&mut self.graph, file_path, format, options)
+    }
+
+    // Excecute the algorithm
+    fn execute(&mut self) -> Result<(), ExecuteError> {
+        let graph = &self.graph;
+
+        // Dijkstra gives wrong answers for negative weights, so refuse those graphs
+        if let Some((from, to)) = graph.negative_edge() {
+            return Err(ExecuteError::NegativeEdge { from, to });
+        }
+
+        (self.shortest_paths, self.predecessors) = dijkstra_csr(graph, self.config.track_paths);
+
+        self.config.check(&self.shortest_paths)
+    }
+
+    // Write the results to a Hashmap to make comparison easy
+    fn get_result(&mut self) -> APSPResult<W> {
+        self.config.result(&mut self.shortest_paths, &mut self.predecessors)
+    }
+
+    fn config_mut(&mut self) -> &mut AlgorithmConfig {
+        &mut self.config
+    }
+}
+
+// Queries for a few sources only run Dijkstra from those sources
+impl<W: Weight> ShortestPathQuery<W> for DijkstraCSR<W> {
+    fn query(&self, query: &Query) -> Result<DistanceTable<W>, ExecuteError> {
+        dijkstra_query(&self.graph, query)
+    }
+}
@@ -0,0 +1,153 @@
+// This is our implemenatation of the Dijkstra algorithm on a rayon thread pool
+// for the all pair shortest path of a graph. Every start node is a separate task, rayon divides
+// them over the threads of the pool and lets idle threads steal work from busy ones
+// 30-01-2024
+
+// Import crates
+use rayon::prelude::*;
+use std::marker::{Send, Sync};
+
+// Import crates from our files
+use crate::algorithms::dijkstra_csr::dijkstra_single_source;
+use crate::base::{
+    load_graph_into, APSPAlgorithm, APSPResult, AlgorithmConfig, DistanceMatrix, ExecuteError,
+    PredecessorMatrix,
+};
+use crate::graph::GraphCSR;
+use crate::labels::Labels;
+use crate::loader::{GraphFormat, LoadError, LoadOptions};
+use crate::matrix::Matrix;
+use crate::pool::Pool;
+use crate::weight::Weight;
+
+/// Dijkstra Algorithm for all start nodes on the threads of the pool
+/// If track_paths is true the predecessor matrix is computed as well, otherwise it is left empty
+pub fn dijkstra_rayon<W: Weight + Send + Sync>(
+    graph: &GraphCSR<W>,
+    pool: &Pool,
+    track_paths: bool,
+) -> (DistanceMatrix<W>, PredecessorMatrix) {
+    let n = graph.node_count;
+    let mut distance_matrix = Matrix::new(n, n, W::INFINITY);
+    let mut predecessor_matrix = if track_paths {
+        Matrix::new(n, n, None)
+    } else {
+        Matrix::default()
+    };
+
+    pool.install(|| {
+        // Row r of the matrices belongs to start node r, every task writes to its own rows
+        let rows = distance_matrix.as_mut_slice().par_chunks_mut(n.max(1)).enumerate();
+        if track_paths {
+            let predecessor_rows = predecessor_matrix.as_mut_slice().par_chunks_mut(n.max(1));
+            rows.zip(predecessor_rows)
+                .for_each(|((start, distance_row), predecessor_row)| {
+                    dijkstra_single_source(graph, start, distance_row, predecessor_row);
+                });
+        } else {
+            // Scratch row for the predecessors, one for every thread
+            rows.for_each_init(Vec::new, |scratch, (start, distance_row)| {
+                scratch.clear();
+                scratch.resize(n, None);
+                dijkstra_single_source(graph, start, distance_row, scratch);
+            });
+        }
+    });
+
+    // Return the distance matrix and the predecessors
+    (distance_matrix, predecessor_matrix)
+}
+
+/// Struct for Dijkstra algorithm on a rayon thread pool
+pub struct DijkstraRayon<W>
+where
+    W: Weight + Send + Sync,
+{
+    /// Struct containing the Graph, the Shortest Path and the pool to run on
+    pub graph: GraphCSR<W>,
+    pub shortest_paths: DistanceMatrix<W>,
+    /// Predecessor matrix, only filled if the paths are tracked
+    pub predecessors: PredecessorMatrix,
+    pub pool: Pool,
+    /// Whether the paths are tracked and the overflow is checked
+    pub config: AlgorithmConfig,
+}
+
+impl<W: Weight + Send + Sync> DijkstraRayon<W> {
+    pub fn new(pool: &Pool) -> Self {
+        Self {
+            graph: GraphCSR::new(),
+            shortest_paths: Matrix::default(),
+            predecessors: Matrix::default(),
+            pool: pool.clone(),
+            config: AlgorithmConfig::new(),
+        }
+    }
+}
+
+// Implementation for the APSPAlgorithm struct
+impl<W: Weight + Send + Sync> APSPAlgorithm<W> for DijkstraRayon<W> {
+    // Load the graph
+    fn load_graph_from(
+        &mut self,
+        file_path: &str,
+        format: GraphFormat,
+        options: &LoadOptions,
+    ) -> Result<Option<Labels>, LoadError> {
+        load_graph_into(&mut self.graph, file_path, format, options)
+    }
+
+    // Excecute the algorithm
+    fn execute(&mut self) -> Result<(), ExecuteError> {
+        let graph = &self.graph;
+
+        // Dijkstra gives wrong answers for negative weights, so refuse those graphs
+        if let Some((from, to)) = graph.negative_edge() {
+            return Err(ExecuteError::NegativeEdge { from, to });
+        }
+
+        (self.shortest_paths, self.predecessors) =
+            dijkstra_rayon(graph, &self.pool, self.config.track_paths);
+
+        self.config.check(&self.shortest_paths)
+    }
+
+    // Write the results to a Hashmap to make comparison easy
+    fn get_result(&mut self) -> APSPResult<W> {
+        self.config.result(&mut self.shortest_paths, &mut self.predecessors)
+    }
+
+    fn config_mut(&mut self) -> &mut AlgorithmConfig {
+        &mut self.config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::dijkstra_csr::dijkstra_csr;
+    use crate::generate::{GraphGenerator, Model};
+
+    #[test]
+    fn rayon_matches_sequential() {
+        let graph: GraphCSR<u32> = GraphGenerator::new(Model::Gnp { nodes: 60, probability: 0.05 })
+            .directed(true)
+            .with_seed(7)
+            .build()
+            .unwrap();
+        for threads in [1, 2, 4] {
+            let pool = Pool::new(threads).unwrap();
+            for track_paths in [false, true] {
+                let (distance, predecessors) = dijkstra_csr(&graph, track_paths);
+                let (rayon_distance, rayon_predecessors) =
+                    dijkstra_rayon(&graph, &pool, track_paths);
+                assert_eq!(rayon_distance, distance, "{} threads", threads);
+                assert_eq!(rayon_predecessors, predecessors, "{} threads", threads);
+            }
+
+            // An empty graph has no rows to hand out
+            let (distance, predecessors) = dijkstra_rayon(&GraphCSR::<u32>::new(), &pool, true);
+            assert_eq!((distance.rows(), predecessors.rows()), (0, 0));
+        }
+    }
+}
@@ -1,51 +1,78 @@
 use std::collections::HashMap;
 
-use num::Num;
 use petgraph::algo::{floyd_warshall, BoundedMeasure};
+use petgraph::visit::Dfs;
 use petgraph::{graph, Graph};
 
-use crate::base::{APSPAlgorithm, APSPResult};
+use crate::base::{load_graph_into, AlgorithmConfig, APSPAlgorithm, APSPResult, ExecuteError};
 use crate::graph::MutByNodeId;
-use crate::loader::FromGraphFile;
+use crate::labels::Labels;
+use crate::loader::{GraphFormat, LoadError, LoadOptions};
+use crate::weight::Weight;
 
 pub struct BaseLineFloydWarshall<W>
 where
-    W: Num + Copy + BoundedMeasure, // BoundedMeasure is only required to call `floyd_warshall`
+    W: Weight + BoundedMeasure, // BoundedMeasure is only required to call `floyd_warshall`
 {
     pub graph: Graph<usize, W>,
     pub shortest_paths: HashMap<(graph::NodeIndex, graph::NodeIndex), W>,
+    /// petgraph does not give the paths and checks for an overflow itself, so the settings are
+    /// not used
+    pub config: AlgorithmConfig,
 }
 
-impl<W: Num + Copy + BoundedMeasure> BaseLineFloydWarshall<W> {
+impl<W: Weight + BoundedMeasure> Default for BaseLineFloydWarshall<W> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<W: Weight + BoundedMeasure> BaseLineFloydWarshall<W> {
     pub fn new() -> Self {
         Self {
             graph: Graph::new(),
             shortest_paths: HashMap::new(),
+            config: AlgorithmConfig::new(),
         }
     }
 }
 
-impl<W: Num + Copy + BoundedMeasure> APSPAlgorithm<W> for BaseLineFloydWarshall<W> {
-    fn load_graph(&mut self, file_path: &str, is_sparse_format: bool) {
-        let graph: Graph<usize, W> = if is_sparse_format {
-            Graph::from_sparse_file(file_path)
-        } else {
-            Graph::from_dense_file(file_path)
-        };
-
-        self.graph = graph;
+impl<W: Weight + BoundedMeasure> APSPAlgorithm<W> for BaseLineFloydWarshall<W> {
+    fn load_graph_from(
+        &mut self,
+        file_path: &str,
+        format: GraphFormat,
+        options: &LoadOptions,
+    ) -> Result<Option<Labels>, LoadError> {
+        load_graph_into(&mut self.graph, file_path, format, options)
     }
 
-    fn execute(&mut self) {
+    fn execute(&mut self) -> Result<(), ExecuteError> {
         let graph = &self.graph;
 
-        self.shortest_paths = floyd_warshall(&graph, |edge| *edge.weight()).unwrap();
+        // petgraph does not tell which nodes are on the negative cycle
+        self.shortest_paths = floyd_warshall(&graph, |edge| *edge.weight())
+            .map_err(|_| ExecuteError::NegativeCycle(Vec::new()))?;
+        Ok(())
     }
 
     fn get_result(&mut self) -> APSPResult<W> {
         let graph = &self.graph;
-        let mut result = APSPResult::new();
+        // The loader adds all nodes of the header, also the ones without edges
+        let mut result = APSPResult::new(graph.node_count());
+        // petgraph starts the unreachable pairs at the maximum value, but adds the negative weights
+        // to it, so look up which pairs have a path instead of checking for the maximum
+        let mut reachable = vec![false; graph.node_count() * graph.node_count()];
+        for start in graph.node_indices() {
+            let mut dfs = Dfs::new(graph, start);
+            while let Some(node) = dfs.next(graph) {
+                reachable[start.index() * graph.node_count() + node.index()] = true;
+            }
+        }
         for ((from, to), cost) in &self.shortest_paths {
+            if !reachable[from.index() * graph.node_count() + to.index()] {
+                continue;
+            }
             let from = graph.get_node_id(*from).unwrap();
             let to = graph.get_node_id(*to).unwrap();
 
@@ -54,4 +81,8 @@ impl<W: Num + Copy + BoundedMeasure> APSPAlgorithm<W> for BaseLineFloydWarshall<
 
         result
     }
+
+    fn config_mut(&mut self) -> &mut AlgorithmConfig {
+        &mut self.config
+    }
 }
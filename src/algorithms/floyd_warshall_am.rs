@@ -3,111 +3,152 @@
 // the all pair shortest path of a graph parsed in as a adjacency matrix
 // 29-01-2024
 
-// Import crates
-use num::Num;
-use std::cmp::PartialOrd;
-use std::fmt::Debug;
+// Import crates from our files
 use crate::graph::GraphAM;
-use crate::base::{APSPAlgorithm, APSPResult};
-use crate::loader::FromGraphFile;
+use crate::algorithms::bellman_ford::negative_cycle_error;
+use crate::base::{
+    has_negative_diagonal, initial_predecessors, load_graph_into, zero_diagonal, APSPAlgorithm,
+    APSPResult, AlgorithmConfig, DistanceMatrix, ExecuteError, PredecessorMatrix,
+};
+use crate::labels::Labels;
+use crate::loader::{GraphFormat, LoadError, LoadOptions};
+use crate::matrix::Matrix;
+use crate::weight::Weight;
 
-// FloydWarshall Algorithm
-pub fn floyd_warshall<W: Num + Copy + PartialOrd + Debug>(
+/// FloydWarshall Algorithm
+/// If track_paths is true the predecessor matrix is computed as well, otherwise it is left empty
+/// Returns an error with the nodes on the cycle if the graph contains a negative cycle
+pub fn floyd_warshall<W: Weight>(
     graph: &GraphAM<W>,
-) -> Vec<Vec<Option<W>>> {
-    // Clone the adjacency matrix to a new distance matrix 
-    let mut distance = graph.adjacency_matrix.clone();
+    track_paths: bool,
+) -> Result<(DistanceMatrix<W>, PredecessorMatrix), ExecuteError> {
+    // Copy the adjacency matrix to a new flat distance matrix
+    let mut distance = graph.distance_matrix();
+
+    // The predecessor of j on the path from i is i itself if there is a direct edge
+    let mut predecessors = if track_paths {
+        initial_predecessors(&graph.adjacency_matrix)
+    } else {
+        Matrix::default()
+    };
 
     // Update the main diagional to all zeros such that the distance to the original point is
     // equal 0
-    for (i, _) in (0..graph.node_count).enumerate() {
-        distance[i][i] = Some(W::zero());
-    }
+    zero_diagonal(&mut distance);
+
+    // Row k does not change in round k since the diagonal is zero, so a copy of it can be used
+    // while the other rows are updated. The inner loop then runs over two plain slices
+    let mut row_k: Vec<W> = Vec::with_capacity(graph.node_count);
+    let mut predecessors_k: Vec<Option<usize>> = Vec::new();
 
     // Loop over all the nodes three times
     for k in 0..graph.node_count {
+        row_k.clear();
+        row_k.extend_from_slice(distance.row(k));
+        if track_paths {
+            predecessors_k.clear();
+            predecessors_k.extend_from_slice(predecessors.row(k));
+        }
+
         for i in 0..graph.node_count {
-            for j in 0..graph.node_count {
-                // Check if there is a value and if so assign to ik or kj
-                if let (Some(ik), Some(kj)) = (distance[i][k], distance[k][j]) {
-                    // Check if there is an distance available, if yes assign to ij.
-                    // otherwise add the sum ik and kj to distance
-                    if let Some(ij) = distance[i][j] {
-                        // If the sum is smaller then update the distance matrix
-                        if ik + kj < ij {
-                            distance[i][j] = Some(ik + kj);
-                        }
-                    } else {
-                        distance[i][j] = Some(ik + kj);
+            // Without a path from i to k there is nothing to improve via k
+            let ik = distance[(i, k)];
+            if ik.is_infinite() {
+                continue;
+            }
+
+            if track_paths {
+                let row_i = distance.row_mut(i);
+                let predecessors_i = predecessors.row_mut(i);
+                for j in 0..graph.node_count {
+                    // If the sum is smaller then update the distance matrix
+                    let sum = ik.add_weight(row_k[j]);
+                    if sum < row_i[j] {
+                        row_i[j] = sum;
+                        // The path to j now goes via k, so take over the predecessor of j from k
+                        predecessors_i[j] = predecessors_k[j];
+                    }
+                }
+            } else {
+                for (ij, &kj) in distance.row_mut(i).iter_mut().zip(&row_k) {
+                    let sum = ik.add_weight(kj);
+                    if sum < *ij {
+                        *ij = sum;
                     }
                 }
             }
         }
+
+        // A negative distance from a node to itself means there is a negative cycle. Stop right
+        // away, otherwise the distances keep decreasing and can overflow
+        if has_negative_diagonal(&distance) {
+            return Err(negative_cycle_error(graph));
+        }
     }
-    // Return distance
-    distance
+    // Return distance and predecessors
+    Ok((distance, predecessors))
 }
 
-// Define FloydWarshall struct
+/// Define FloydWarshall struct
 pub struct FloydWarshall<W>
 where
-    W: Num + Copy + Debug,
+    W: Weight,
 {
-    // Define a graph and shortest_paths
+    /// Define a graph and shortest_paths
     pub graph: GraphAM<W>,
-    pub shortest_paths: Vec<Vec<Option<W>>>,
+    pub shortest_paths: DistanceMatrix<W>,
+    /// Predecessor matrix, only filled if the paths are tracked
+    pub predecessors: PredecessorMatrix,
+    /// Whether the paths are tracked and the overflow is checked
+    pub config: AlgorithmConfig,
 }
 
 // Implementation for Floywd Warshall struct
-impl<W: Num + Copy + PartialOrd + Debug> FloydWarshall<W> {
-    // Function to implement an empty struct
+impl<W: Weight> Default for FloydWarshall<W> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<W: Weight> FloydWarshall<W> {
+    /// Function to implement an empty struct
     pub fn new() -> Self {
         Self {
             graph: GraphAM::new(),
-            shortest_paths: Vec::new(),
+            shortest_paths: Matrix::default(),
+            predecessors: Matrix::default(),
+            config: AlgorithmConfig::new(),
         }
     }
 }
 
 // Define the APSPAlgorithm for FW
-impl<W: Num + Copy + PartialOrd + Debug> APSPAlgorithm<W> for FloydWarshall<W> {
+impl<W: Weight> APSPAlgorithm<W> for FloydWarshall<W> {
     // Load the graph from a file using the Graph struct
-    fn load_graph(&mut self, file_path: &str, is_sparse_format: bool) {
-        let graph: GraphAM<W> = if is_sparse_format {
-            GraphAM::from_sparse_file(file_path)
-        } else {
-            GraphAM::from_dense_file(file_path)
-        };
-        // Return the graph
-        self.graph = graph;
+    fn load_graph_from(
+        &mut self,
+        file_path: &str,
+        format: GraphFormat,
+        options: &LoadOptions,
+    ) -> Result<Option<Labels>, LoadError> {
+        load_graph_into(&mut self.graph, file_path, format, options)
     }
 
     // Function to execute the FW algorithm
-    fn execute(&mut self) {
+    fn execute(&mut self) -> Result<(), ExecuteError> {
         // input the graph to the FW 
         let graph = &self.graph;
-        self.shortest_paths = floyd_warshall(graph);
+        (self.shortest_paths, self.predecessors) = floyd_warshall(graph, self.config.track_paths)?;
+
+        self.config.check(&self.shortest_paths)
     }
 
     // Put the result in a Hashmap to be able to compare with other programs
     fn get_result(&mut self) -> APSPResult<W> {
+        self.config.result(&mut self.shortest_paths, &mut self.predecessors)
+    }
 
-        // Get the graph and the SP
-        let graph = &self.graph;
-        let shortest_paths = &self.shortest_paths;
-
-        // Write into APSPResults
-        let mut result = APSPResult::new();
-        for (i, row) in shortest_paths.iter().enumerate().take(graph.node_count) {
-            for (j, &value) in row.iter().enumerate().take(graph.node_count) {
-                if let Some(value) = value {
-                    result.add(i, j, value);
-                }
-            }
-        }
-        
-
-        // Return result
-        result
+    fn config_mut(&mut self) -> &mut AlgorithmConfig {
+        &mut self.config
     }
 }
@@ -3,104 +3,119 @@
 // to parallelize the FW algorithm in another program.
 // 30-01-2023
 
-// Import crates
-use num::Num;
-use std::clone::Clone;
-use std::cmp::PartialOrd;
-use std::fmt::Debug;
-use std::sync::Arc;
-
 // Import crates from our other files
-use crate::base::{APSPAlgorithm, APSPResult};
+use crate::algorithms::bellman_ford::negative_cycle_error;
+use crate::base::{
+    has_negative_diagonal, initial_predecessors, load_graph_into, zero_diagonal, APSPAlgorithm,
+    APSPResult, AlgorithmConfig, DistanceMatrix, ExecuteError, PredecessorMatrix,
+};
 use crate::fwb_function::*;
 use crate::graph::GraphAM;
-use crate::loader::FromGraphFile;
+use crate::labels::Labels;
+use crate::loader::{GraphFormat, LoadError, LoadOptions};
+use crate::matrix::Matrix;
+use crate::weight::Weight;
 
-// Struct for the Floyd Warshall Blocked algorithm
+/// Struct for the Floyd Warshall Blocked algorithm
 pub struct FloydWarshallBlock<W>
 where
-    W: Num + Copy + Debug,
+    W: Weight,
 {
-    // Define the graph, shortest path and the number of blocks
+    /// Define the graph, shortest path and the number of blocks
     pub graph: GraphAM<W>,
-    pub shortest_paths: Vec<Vec<Option<W>>>,
+    pub shortest_paths: DistanceMatrix<W>,
+    /// Predecessor matrix, only filled if the paths are tracked
+    pub predecessors: PredecessorMatrix,
     pub num_blocks: usize,
+    /// Whether the paths are tracked and the overflow is checked
+    pub config: AlgorithmConfig,
 }
 
 // Implement a function to compute an empty struct with the number of blocks assigned
-impl<W: Num + Copy + PartialOrd + Debug + Clone> FloydWarshallBlock<W> {
+impl<W: Weight> FloydWarshallBlock<W> {
     pub fn new(blocks: usize) -> Self {
         Self {
             graph: GraphAM::new(),
-            shortest_paths: Vec::new(),
+            shortest_paths: Matrix::default(),
+            predecessors: Matrix::default(),
             num_blocks: blocks,
+            config: AlgorithmConfig::new(),
         }
     }
 }
 
 // Implement the struct for the APSPAlgorithm struct
-impl<W: Num + Copy + PartialOrd + Debug + Clone> APSPAlgorithm<W> for FloydWarshallBlock<W> {
+impl<W: Weight> APSPAlgorithm<W> for FloydWarshallBlock<W> {
     // Load the graph from a file
-    fn load_graph(&mut self, file_path: &str, is_sparse_format: bool) {
-        let graph: GraphAM<W> = if is_sparse_format {
-            GraphAM::from_sparse_file(file_path)
-        } else {
-            GraphAM::from_dense_file(file_path)
-        };
-
-        self.graph = graph;
+    fn load_graph_from(
+        &mut self,
+        file_path: &str,
+        format: GraphFormat,
+        options: &LoadOptions,
+    ) -> Result<Option<Labels>, LoadError> {
+        load_graph_into(&mut self.graph, file_path, format, options)
     }
 
     // Excecute the Floyd warshall blocked function
-    fn execute(&mut self) {
+    fn execute(&mut self) -> Result<(), ExecuteError> {
         let graph = &self.graph;
-        self.shortest_paths = floyd_warshall_blocked(graph, self.num_blocks);
+        (self.shortest_paths, self.predecessors) =
+            floyd_warshall_blocked(graph, self.num_blocks, self.config.track_paths)?;
+
+        self.config.check(&self.shortest_paths)
     }
 
     // Write the result to a Hashmap to be able to compare results
     fn get_result(&mut self) -> APSPResult<W> {
-        let graph = &self.graph;
-        let shortest_paths = &self.shortest_paths;
+        self.config.result(&mut self.shortest_paths, &mut self.predecessors)
+    }
 
-        let mut result = APSPResult::new();
-        for (i, row) in shortest_paths.iter().enumerate().take(graph.node_count) {
-            for (j, &value) in row.iter().enumerate().take(graph.node_count) {
-                if let Some(value) = value {
-                    result.add(i, j, value);
-                }
-            }
-        }
-        // Return the results
-        result
+    fn config_mut(&mut self) -> &mut AlgorithmConfig {
+        &mut self.config
     }
 }
 
 // A function for the floyd warshall blocked algorithm
-fn floyd_warshall_blocked<W: Num + Copy + PartialOrd + Debug>(
+// If track_paths is true the predecessor matrix is computed as well, otherwise it is left empty
+// Returns an error with the nodes on the cycle if the graph contains a negative cycle
+fn floyd_warshall_blocked<W: Weight>(
     graph: &GraphAM<W>,
     num_blocks: usize,
-) -> Vec<Vec<Option<W>>> {
-    // Clone the adjacency matrix to the distance matrix
-    let mut distance = graph.adjacency_matrix.to_owned();
-
-    // Read the number of nodes and number of nodes per block
+    track_paths: bool,
+) -> Result<(DistanceMatrix<W>, PredecessorMatrix), ExecuteError> {
+    // Copy the adjacency matrix to the flat distance matrix
+    let mut distance = graph.distance_matrix();
+
+    // The path matrix is blocked in exactly the same way as the distance matrix
+    let mut paths = if track_paths {
+        initial_paths(&initial_predecessors(&graph.adjacency_matrix))
+    } else {
+        Matrix::default()
+    };
+
+    // Read the number of nodes and number of nodes per block, a block has at least one node also
+    // if there are more blocks than nodes
     let n = graph.node_count;
-    let block_size = n / num_blocks;
+    let block_size = n.div_ceil(num_blocks.max(1)).max(1);
 
     // Put all the values in the main diagional to be zero
-    for (i, _) in (0..n).enumerate() {
-        distance[i][i] = Some(W::zero());
-    }
+    zero_diagonal(&mut distance);
 
     // Define the number of blocks
-    let blocks = (n as f64 / block_size as f64).ceil() as usize;
+    let blocks = n.div_ceil(block_size);
 
     // Loop over all the blocks
     for k in 0..blocks {
         // Slice the matrix and compute FW on the smaller matrix
-        let akk = slice_matrix_block(&distance, k, k, block_size);
-        let akk = floyd_warshall_in_place1(&akk, block_size);
-        let akk_move = Arc::new(&akk);
+        let mut akk = slice_matrix_block(&distance, k, k, block_size);
+        let pkk = if track_paths {
+            let mut pkk = slice_matrix_block(&paths, k, k, block_size);
+            floyd_warshall_in_place1_paths(&mut akk, &mut pkk);
+            pkk
+        } else {
+            floyd_warshall_in_place1(&mut akk);
+            Matrix::default()
+        };
 
         // Loop over all the blocks
         for j in 0..blocks {
@@ -108,15 +123,21 @@ fn floyd_warshall_blocked<W: Num + Copy + PartialOrd + Debug>(
             if j == k {
                 continue;
             }
+
             // Slice matrix akj
-            let akj = slice_matrix_block(&distance, k, j, block_size);
+            let mut akj = slice_matrix_block(&distance, k, j, block_size);
 
             // Compute the min max matrix multiplication for the akj = akk * akj matrix
-            let akj = floyd_warshall_in_place2(&akj, &akk, block_size);
+            if track_paths {
+                let mut pkj = slice_matrix_block(&paths, k, j, block_size);
+                floyd_warshall_in_place2_paths(&mut akj, &mut pkj, &akk, &pkk);
+                write_back_to_distance(&mut paths, &pkj, k, j, block_size);
+            } else {
+                floyd_warshall_in_place2(&mut akj, &akk);
+            }
 
             // Write the results back to the distance matrix
-            // write_back_to_distance(&mut distance, &akj, k * b, (k + 1) * b, j * b, (j + 1) * b);
-            write_back_to_distance(&mut distance, &akj, k * block_size, j * block_size);
+            write_back_to_distance(&mut distance, &akj, k, j, block_size);
         }
 
         // Loop over all the blocks
@@ -127,10 +148,17 @@ fn floyd_warshall_blocked<W: Num + Copy + PartialOrd + Debug>(
             }
 
             // Slice matrix aik
-            let aik = slice_matrix_block(&distance, i, k, block_size);
+            let mut aik = slice_matrix_block(&distance, i, k, block_size);
 
             // Compute aik using the min max matrix multiplication
-            let aik = floyd_warshall_in_place3(&aik, &akk_move, block_size);
+            let pik = if track_paths {
+                let mut pik = slice_matrix_block(&paths, i, k, block_size);
+                floyd_warshall_in_place3_paths(&mut aik, &mut pik, &akk, &pkk);
+                pik
+            } else {
+                floyd_warshall_in_place3(&mut aik, &akk);
+                Matrix::default()
+            };
 
             // Loop over all the blocks
             for j in 0..blocks {
@@ -138,21 +166,60 @@ fn floyd_warshall_blocked<W: Num + Copy + PartialOrd + Debug>(
                 if j == k {
                     continue;
                 }
-                // Slice matrix aij and akj from the distance matrix
-                let aij = slice_matrix_block(&distance, i, j, block_size);
-                let akj = slice_matrix_immut(&distance, k, j, block_size);
-                let aij = floyd_warshall_in_place4(&aij, &aik, &akj, block_size);
 
-                // Compute aij with the min max matrix multiplication and write to the small distance matrix
-                write_back_to_distance(&mut distance, &aij, i * block_size, j * block_size);
+                // Slice matrix aij and akj from the distance matrix
+                let mut aij = slice_matrix_block(&distance, i, j, block_size);
+                let akj = slice_matrix_block(&distance, k, j, block_size);
+
+                // Compute aij with the min max matrix multiplication and write to the distance matrix
+                if track_paths {
+                    let mut pij = slice_matrix_block(&paths, i, j, block_size);
+                    let pkj = slice_matrix_block(&paths, k, j, block_size);
+                    floyd_warshall_in_place4_paths(&mut aij, &mut pij, &aik, &pik, &akj, &pkj);
+                    write_back_to_distance(&mut paths, &pij, i, j, block_size);
+                } else {
+                    floyd_warshall_in_place4(&mut aij, &aik, &akj);
+                }
+                write_back_to_distance(&mut distance, &aij, i, j, block_size);
             }
+
             // Write aik back to the distance matrix
-            write_back_to_distance(&mut distance, &aik, i * block_size, k * block_size);
+            write_back_to_distance(&mut distance, &aik, i, k, block_size);
+            if track_paths {
+                write_back_to_distance(&mut paths, &pik, i, k, block_size);
+            }
         }
+
         // Write akk back to the distance matrix
-        write_back_to_distance(&mut distance, &akk, k * block_size, k * block_size);
+        write_back_to_distance(&mut distance, &akk, k, k, block_size);
+        if track_paths {
+            write_back_to_distance(&mut paths, &pkk, k, k, block_size);
+        }
+
+        // A negative distance from a node to itself means there is a negative cycle. Stop right
+        // away, otherwise the distances keep decreasing and can overflow
+        if has_negative_diagonal(&distance) {
+            return Err(negative_cycle_error(graph));
+        }
     }
 
-    // return the distance matrix
-    distance
+    // return the distance and predecessor matrix
+    Ok((distance, paths_to_predecessors(&paths)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn more_blocks_than_nodes() {
+        let mut graph = GraphAM::with_capacity(3);
+        graph.add_edge(0, 1, 2u32);
+        graph.add_edge(1, 2, 3);
+        for num_blocks in [0, 1, 2, 3, 10] {
+            let (distance, _) = floyd_warshall_blocked(&graph, num_blocks, false).unwrap();
+            assert_eq!(distance[(0, 2)], 5);
+            assert!(distance[(2, 0)].is_infinite());
+        }
+    }
 }
@@ -1,208 +1,195 @@
 // This is our implemenatation of the Floyd-Warshall Blocked parallel algorithm
 // The implementation uses the advantages of the min max matrix multiplication to be able
 // to parallelize the FW algorithm.
+// A fixed pool of worker threads lives for the whole run. The matrix is split in tiles which the
+// workers update in place, every round k has three phases separated by a barrier: the diagonal
+// tile, the other tiles in row and column k, and all remaining tiles
 // 30-01-2024
 
 // Import crates
-use num::Num;
-use std::clone::Clone;
-use std::cmp::PartialOrd;
-use std::fmt::Debug;
 use std::marker::{Send, Sync};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Barrier;
 use std::thread;
 
 // Import crates from our other files
-use crate::base::{APSPAlgorithm, APSPResult};
+use crate::algorithms::bellman_ford::negative_cycle_error;
+use crate::base::{
+    initial_predecessors, load_graph_into, zero_diagonal, APSPAlgorithm, APSPResult,
+    AlgorithmConfig, DistanceMatrix, ExecuteError, PredecessorMatrix,
+};
 use crate::fwb_function::*;
 use crate::graph::GraphAM;
-use crate::loader::FromGraphFile;
+use crate::labels::Labels;
+use crate::loader::{GraphFormat, LoadError, LoadOptions};
+use crate::matrix::Matrix;
+use crate::weight::Weight;
 
-// Struct for the Floyd Warshall Blocked parallel algorithm
+/// Struct for the Floyd Warshall Blocked parallel algorithm
 pub struct FloydWarshallBlockPar<W>
 where
-    W: Num + Copy + Debug + Send + Sync + 'static,
+    W: Weight + Send + Sync + 'static,
 {
-    // Define the graph, shortest path and the number of cores
+    /// Define the graph, shortest path, the number of threads and the number of nodes per block
     pub graph: GraphAM<W>,
-    pub shortest_paths: Vec<Vec<Option<W>>>,
-    pub num_cores: usize,
+    pub shortest_paths: DistanceMatrix<W>,
+    /// Predecessor matrix, only filled if the paths are tracked
+    pub predecessors: PredecessorMatrix,
+    pub num_threads: usize,
+    pub block_size: usize,
+    /// Whether the paths are tracked and the overflow is checked
+    pub config: AlgorithmConfig,
 }
 
-// Implement a function to compute an empty struct with the number of cores assigned
-impl<W: Num + Copy + PartialOrd + Debug + Clone + Send + Sync + 'static> FloydWarshallBlockPar<W> {
-    pub fn new(cores: usize) -> Self {
+// Implement a function to compute an empty struct with the number of threads and the block size
+// assigned. The two are independent, more blocks than threads gives a better load balance
+impl<W: Weight + Send + Sync + 'static> FloydWarshallBlockPar<W> {
+    pub fn new(threads: usize, block_size: usize) -> Self {
         Self {
             graph: GraphAM::new(),
-            shortest_paths: Vec::new(),
-            num_cores: cores,
+            shortest_paths: Matrix::default(),
+            predecessors: Matrix::default(),
+            num_threads: threads,
+            block_size,
+            config: AlgorithmConfig::new(),
         }
     }
 }
 
 // Implement the struct for the APSPAlgorithm struct
-impl<W: Num + Copy + PartialOrd + Debug + Clone + Send + Sync + 'static> APSPAlgorithm<W>
+impl<W: Weight + Send + Sync + 'static> APSPAlgorithm<W>
     for FloydWarshallBlockPar<W>
 {
     // Load the graph from a file
-    fn load_graph(&mut self, file_path: &str, is_sparse_format: bool) {
-        let graph: GraphAM<W> = if is_sparse_format {
-            GraphAM::from_sparse_file(file_path)
-        } else {
-            GraphAM::from_dense_file(file_path)
-        };
-
-        self.graph = graph;
+    fn load_graph_from(
+        &mut self,
+        file_path: &str,
+        format: GraphFormat,
+        options: &LoadOptions,
+    ) -> Result<Option<Labels>, LoadError> {
+        load_graph_into(&mut self.graph, file_path, format, options)
     }
 
     // Excecute the Floyd warshall blocked parallel function
-    fn execute(&mut self) {
-        let graph = &self.graph;
-        self.shortest_paths = floyd_warshall_blocked_par(graph, self.num_cores);
+    fn execute(&mut self) -> Result<(), ExecuteError> {
+        let (graph, track_paths) = (&self.graph, self.config.track_paths);
+        (self.shortest_paths, self.predecessors) =
+            floyd_warshall_blocked_par(graph, self.num_threads, self.block_size, track_paths)?;
+
+        self.config.check(&self.shortest_paths)
     }
 
     // Write the result to a Hashmap to be able to compare results
     fn get_result(&mut self) -> APSPResult<W> {
-        let graph = &self.graph;
-        let shortest_paths = &self.shortest_paths;
-
-        let mut result = APSPResult::new();
-        for (i, row) in shortest_paths.iter().enumerate().take(graph.node_count) {
-            for (j, &value) in row.iter().enumerate().take(graph.node_count) {
-                if let Some(value) = value {
-                    result.add(i, j, value);
-                }
-            }
-        }
+        self.config.result(&mut self.shortest_paths, &mut self.predecessors)
+    }
 
-        // Return the results
-        result
+    fn config_mut(&mut self) -> &mut AlgorithmConfig {
+        &mut self.config
     }
 }
 
 // A function for the parallel floyd warshall blocked algorithm
-fn floyd_warshall_blocked_par<W: Num + Copy + PartialOrd + Debug + Send + Sync + 'static>(
+// If track_paths is true the predecessor matrix is computed as well, otherwise it is left empty
+// Returns an error with the nodes on the cycle if the graph contains a negative cycle
+fn floyd_warshall_blocked_par<W: Weight + Send + Sync>(
     graph: &GraphAM<W>,
-    num_cores: usize,
-) -> Vec<Vec<Option<W>>> {
-    // Clone the adjacency matrix to the distance matrix
-    let mut distance = graph.adjacency_matrix.to_owned();
-
-    // Read the number of nodes and number of nodes per block
-    let n = graph.node_count;
-    let block_size = (n as f64 / num_cores as f64).ceil() as usize;
-    let og_block_size = (n as f64 / num_cores as f64).ceil() as usize;
+    num_threads: usize,
+    block_size: usize,
+    track_paths: bool,
+) -> Result<(DistanceMatrix<W>, PredecessorMatrix), ExecuteError> {
+    // Copy the adjacency matrix to the flat distance matrix
+    let mut distance = graph.distance_matrix();
+
+    // The path matrix is blocked in exactly the same way as the distance matrix
+    let mut paths = if track_paths {
+        initial_paths(&initial_predecessors(&graph.adjacency_matrix))
+    } else {
+        Matrix::default()
+    };
+
+    // A block has at least one node
+    let block_size = block_size.max(1);
 
     // Put all the values in the main diagional to be zero
-    for (i, _) in (0..n).enumerate() {
-        distance[i][i] = Some(W::zero());
-    }
-
-    let num_cores = (n as f64 / block_size as f64).ceil() as usize;
-
-    // Loop over all the blocks
-    for k in 0..num_cores {
-        // Slice the matrix and compute FW on the smaller matrix
-        let akk = slice_matrix_block(&distance, k, k, block_size);
-
-        let akk = floyd_warshall_in_place1(&akk, block_size);
-        let akk_move = Arc::new(akk.clone());
-
-        // Define empty vector for the handles
-        let mut handles = vec![];
-
-        // Loop over all the blocks
-        for j in 0..num_cores {
-            // If it is the main diagional then ignore and continue
-            if j == k {
-                continue;
-            }
-            // Slice matrix akj and clone akk
-            let akj = slice_matrix_block(&distance, k, j, block_size);
-
-            let akk_move = akk_move.clone();
-
-            // Compute the min max matrix multiplication for the akj = akk * akj matrix
-            // on each thread
-            let handle =
-                thread::spawn(move || (floyd_warshall_in_place2(&akj, &akk_move, block_size), j));
-            // Pusht the handle to the handles vector
-            handles.push(handle);
-        }
-
-        // Return the values from the handles and write back to the original distance matrix
-        for handle in handles {
-            let (akj, j) = handle.join().expect("Thread Panicked!");
-            write_back_to_distance(&mut distance, &akj, k * block_size, j * block_size);
-        }
+    zero_diagonal(&mut distance);
+
+    let tiles = Tiles::split(&distance, &paths, block_size);
+    let blocks = tiles.blocks;
+
+    // There is no use in more threads than tiles in the last phase of a round
+    let num_threads = num_threads.clamp(1, (blocks * blocks).max(1));
+    let barrier = Barrier::new(num_threads);
+    let negative_cycle = AtomicBool::new(false);
+
+    thread::scope(|scope| {
+        for worker in 0..num_threads {
+            let (tiles, barrier, negative_cycle) = (&tiles, &barrier, &negative_cycle);
+            scope.spawn(move || {
+                for k in 0..blocks {
+                    // Phase 1: the diagonal tile, done by the first worker
+                    if worker == 0 && tiles.update_diagonal(k, track_paths) {
+                        negative_cycle.store(true, Ordering::Relaxed);
+                    }
+                    barrier.wait();
 
-        // Define again an empty handles vector
-        let mut handles = vec![];
-
-        let akk_move = Arc::new(akk.clone());
-
-        // Loop over all the blocks
-        for i in 0..num_cores {
-            // If i is on the main diagional then continue to the next i
-            if i == k {
-                continue;
-            }
-            // Slice matrix ai and ak and clone akk
-            let ai = slice_matrix(&distance, i * block_size, (i + 1) * block_size, 0, n);
-            let ak = slice_matrix(&distance, k * block_size, (k + 1) * block_size, 0, n);
-
-            let akk_move: Arc<Vec<Vec<Option<W>>>> = akk_move.clone();
-
-            // spawn a new thread which returns a small distance matrix
-            let handle = thread::spawn(move || {
-                let b_rows = if i == num_cores - 1 && n % og_block_size != 0 {
-                    n % og_block_size
-                } else {
-                    block_size
-                };
-
-                // Define empty distance matrix of size (n x b)
-                let mut distance_matrix: Vec<Vec<Option<W>>> = vec![vec![None; n]; b_rows];
-                // slice matrix aik from slice ai
-                let aik = slice_matrix_block(&ai, 0, k, block_size);
-
-                // Compute aik using the min max matrix multiplication
-                let aik = floyd_warshall_in_place3(&aik, &akk_move, block_size);
-
-                // Loop over all the blocks
-                for j in 0..num_cores {
-                    // If j == k write the result to our small distance matrix and continue to the next value
-                    if j == k {
-                        write_back_to_distance(&mut distance_matrix, &aik, 0, j * block_size);
-                        continue;
+                    // Phase 2 and 3, every worker takes every num_threads-th tile
+                    for (i, j) in row_column_tiles(k, blocks).skip(worker).step_by(num_threads) {
+                        tiles.update_row_column(k, i, j, track_paths);
                     }
-                    // Slice matrix aij and akj from slices ai and ak
-                    let aij = slice_matrix_block(&ai, 0, j, block_size);
-                    let akj = slice_matrix_immut(&ak, 0, j, block_size);
+                    barrier.wait();
 
-                    // Compute aij with the min max matrix multiplication and write to the small distance matrix
-                    let aij = floyd_warshall_in_place4(&aij, &aik, &akj, block_size);
-                    write_back_to_distance(&mut distance_matrix, &aij, 0, j * block_size)
+                    for (i, j) in other_tiles(k, blocks).skip(worker).step_by(num_threads) {
+                        if tiles.update_other(k, i, j, track_paths) {
+                            negative_cycle.store(true, Ordering::Relaxed);
+                        }
+                    }
+                    barrier.wait();
+
+                    // A negative distance from a node to itself means there is a negative cycle.
+                    // Stop right away, otherwise the distances keep decreasing and can overflow.
+                    // The flag is read before the next round can set it, so that every worker
+                    // takes the same decision
+                    let stop = negative_cycle.load(Ordering::Relaxed);
+                    barrier.wait();
+                    if stop {
+                        break;
+                    }
                 }
-                // Return the distance matrix and the current index i
-                (distance_matrix, i)
             });
-
-            // Push the handle to the handles vector
-            handles.push(handle)
         }
+    });
+
+    if negative_cycle.into_inner() {
+        return Err(negative_cycle_error(graph));
+    }
 
-        // Loop over all handles to return the values and write to our distance matrix
-        for handle in handles {
-            let (slice, i) = handle.join().expect("Thread panicked!");
+    // Copy the tiles back to the distance matrix
+    tiles.join(&mut distance, &mut paths, block_size);
 
-            write_back_to_distance(&mut distance, &slice, i * block_size, 0)
-        }
+    // Return the distance and predecessor matrix
+    Ok((distance, paths_to_predecessors(&paths)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        // write akk to the distance matrix
-        write_back_to_distance(&mut distance, &akk, k * block_size, k * block_size);
+    // A cycle 0 -> 1 -> ... -> 0 with a negative total weight, spread over several tiles
+    fn negative_cycle_graph(nodes: usize) -> GraphAM<i32> {
+        let mut graph = GraphAM::with_capacity(nodes);
+        for node in 0..nodes {
+            graph.add_edge(node, (node + 1) % nodes, if node == 0 { -(nodes as i32) } else { 1 });
+        }
+        graph
     }
 
-    // Return the distance matrix
-    distance
+    #[test]
+    fn negative_cycle_stops_every_worker() {
+        let graph = negative_cycle_graph(12);
+        for _ in 0..500 {
+            let result = floyd_warshall_blocked_par(&graph, 4, 2, false);
+            assert!(matches!(result, Err(ExecuteError::NegativeCycle(_))));
+        }
+    }
 }
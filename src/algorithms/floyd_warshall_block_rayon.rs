@@ -0,0 +1,141 @@
+// This is our implemenatation of the Floyd-Warshall Blocked algorithm on a rayon thread pool
+// The tiles are the same as for the parallel blocked version, but instead of a fixed division of
+// the tiles over the threads every tile is a rayon task
+// 30-01-2024
+
+// Import crates
+use rayon::prelude::*;
+use std::marker::{Send, Sync};
+
+// Import crates from our other files
+use crate::algorithms::bellman_ford::negative_cycle_error;
+use crate::base::{
+    initial_predecessors, load_graph_into, zero_diagonal, APSPAlgorithm, APSPResult,
+    AlgorithmConfig, DistanceMatrix, ExecuteError, PredecessorMatrix,
+};
+use crate::fwb_function::*;
+use crate::graph::GraphAM;
+use crate::labels::Labels;
+use crate::loader::{GraphFormat, LoadError, LoadOptions};
+use crate::matrix::Matrix;
+use crate::pool::Pool;
+use crate::weight::Weight;
+
+/// Blocked FW on the threads of the pool, with tiles of (block_size x block_size)
+/// If track_paths is true the predecessor matrix is computed as well, otherwise it is left empty
+/// Returns an error with the nodes on the cycle if the graph contains a negative cycle
+pub fn floyd_warshall_blocked_rayon<W: Weight + Send + Sync>(
+    graph: &GraphAM<W>,
+    pool: &Pool,
+    block_size: usize,
+    track_paths: bool,
+) -> Result<(DistanceMatrix<W>, PredecessorMatrix), ExecuteError> {
+    // Copy the adjacency matrix to the flat distance matrix
+    let mut distance = graph.distance_matrix();
+    let mut paths = if track_paths {
+        initial_paths(&initial_predecessors(&graph.adjacency_matrix))
+    } else {
+        Matrix::default()
+    };
+    zero_diagonal(&mut distance);
+
+    // A block has at least one node
+    let block_size = block_size.max(1);
+    let tiles = Tiles::split(&distance, &paths, block_size);
+    let blocks = tiles.blocks;
+
+    let negative_cycle = pool.install(|| {
+        for k in 0..blocks {
+            if tiles.update_diagonal(k, track_paths) {
+                return true;
+            }
+
+            let row_column: Vec<(usize, usize)> = row_column_tiles(k, blocks).collect();
+            row_column
+                .par_iter()
+                .for_each(|&(i, j)| tiles.update_row_column(k, i, j, track_paths));
+
+            // Every tile has to be updated, so combine the results instead of stopping at the
+            // first negative diagonal
+            let other: Vec<(usize, usize)> = other_tiles(k, blocks).collect();
+            let negative = other
+                .par_iter()
+                .map(|&(i, j)| tiles.update_other(k, i, j, track_paths))
+                .reduce(|| false, |a, b| a || b);
+            if negative {
+                return true;
+            }
+        }
+        false
+    });
+
+    if negative_cycle {
+        return Err(negative_cycle_error(graph));
+    }
+
+    // Copy the tiles back to the distance matrix
+    tiles.join(&mut distance, &mut paths, block_size);
+
+    // Return the distance and predecessor matrix
+    Ok((distance, paths_to_predecessors(&paths)))
+}
+
+/// Struct for the Floyd Warshall Blocked algorithm on a rayon thread pool
+pub struct FloydWarshallBlockRayon<W>
+where
+    W: Weight + Send + Sync,
+{
+    /// Define the graph, shortest path, the pool and the number of nodes per block
+    pub graph: GraphAM<W>,
+    pub shortest_paths: DistanceMatrix<W>,
+    /// Predecessor matrix, only filled if the paths are tracked
+    pub predecessors: PredecessorMatrix,
+    pub pool: Pool,
+    pub block_size: usize,
+    /// Whether the paths are tracked and the overflow is checked
+    pub config: AlgorithmConfig,
+}
+
+impl<W: Weight + Send + Sync> FloydWarshallBlockRayon<W> {
+    pub fn new(pool: &Pool, block_size: usize) -> Self {
+        Self {
+            graph: GraphAM::new(),
+            shortest_paths: Matrix::default(),
+            predecessors: Matrix::default(),
+            pool: pool.clone(),
+            block_size,
+            config: AlgorithmConfig::new(),
+        }
+    }
+}
+
+// Implement the struct for the APSPAlgorithm struct
+impl<W: Weight + Send + Sync> APSPAlgorithm<W> for FloydWarshallBlockRayon<W> {
+    // Load the graph from a file
+    fn load_graph_from(
+        &mut self,
+        file_path: &str,
+        format: GraphFormat,
+        options: &LoadOptions,
+    ) -> Result<Option<Labels>, LoadError> {
+        load_graph_into(&mut self.graph, file_path, format, options)
+    }
+
+    // Excecute the Floyd warshall blocked function on the pool
+    fn execute(&mut self) -> Result<(), ExecuteError> {
+        let (graph, track_paths) = (&self.graph, self.config.track_paths);
+        (self.shortest_paths, self.predecessors) =
+            floyd_warshall_blocked_rayon(graph, &self.pool, self.block_size, track_paths)?;
+
+        self.config.check(&self.shortest_paths)
+    }
+
+    // Write the result to a Hashmap to be able to compare results
+    fn get_result(&mut self) -> APSPResult<W> {
+        self.config.result(&mut self.shortest_paths, &mut self.predecessors)
+    }
+
+    fn config_mut(&mut self) -> &mut AlgorithmConfig {
+        &mut self.config
+    }
+}
@@ -0,0 +1,162 @@
+// This is our implemenatation of the Floyd-Warshall algorithm on a rayon thread pool
+// In round k every row i is only updated with row k, which does not change in round k. So all
+// rows can be updated in parallel, with a synchronisation point after every round
+// 30-01-2024
+
+// Import crates
+use rayon::prelude::*;
+use std::marker::{Send, Sync};
+
+// Import crates from our files
+use crate::algorithms::bellman_ford::negative_cycle_error;
+use crate::base::{
+    has_negative_diagonal, initial_predecessors, load_graph_into, zero_diagonal, APSPAlgorithm,
+    APSPResult, AlgorithmConfig, DistanceMatrix, ExecuteError, PredecessorMatrix,
+};
+use crate::graph::GraphAM;
+use crate::labels::Labels;
+use crate::loader::{GraphFormat, LoadError, LoadOptions};
+use crate::matrix::Matrix;
+use crate::pool::Pool;
+use crate::weight::Weight;
+
+/// Row-parallel FW on the threads of the pool
+/// If track_paths is true the predecessor matrix is computed as well, otherwise it is left empty
+/// Returns an error with the nodes on the cycle if the graph contains a negative cycle
+pub fn floyd_warshall_rayon<W: Weight + Send + Sync>(
+    graph: &GraphAM<W>,
+    pool: &Pool,
+    track_paths: bool,
+) -> Result<(DistanceMatrix<W>, PredecessorMatrix), ExecuteError> {
+    let n = graph.node_count;
+
+    // Copy the adjacency matrix to a new flat distance matrix
+    let mut distance = graph.distance_matrix();
+    let mut predecessors = if track_paths {
+        initial_predecessors(&graph.adjacency_matrix)
+    } else {
+        Matrix::default()
+    };
+    zero_diagonal(&mut distance);
+
+    // Copy of row k, which is read by all threads while they update the other rows
+    let mut row_k: Vec<W> = Vec::with_capacity(n);
+    let mut predecessors_k: Vec<Option<usize>> = Vec::new();
+
+    let negative_cycle = pool.install(|| {
+        for k in 0..n {
+            row_k.clear();
+            row_k.extend_from_slice(distance.row(k));
+            if track_paths {
+                predecessors_k.clear();
+                predecessors_k.extend_from_slice(predecessors.row(k));
+            }
+            let (row_k, predecessors_k) = (&row_k, &predecessors_k);
+
+            let rows = distance.as_mut_slice().par_chunks_mut(n);
+            if track_paths {
+                let predecessor_rows = predecessors.as_mut_slice().par_chunks_mut(n);
+                rows.zip(predecessor_rows).for_each(|(row_i, predecessors_i)| {
+                    // Without a path from i to k there is nothing to improve via k
+                    let ik = row_i[k];
+                    if ik.is_infinite() {
+                        return;
+                    }
+                    for j in 0..n {
+                        let sum = ik.add_weight(row_k[j]);
+                        if sum < row_i[j] {
+                            row_i[j] = sum;
+                            // The path to j now goes via k, so take over the predecessor of j
+                            predecessors_i[j] = predecessors_k[j];
+                        }
+                    }
+                });
+            } else {
+                rows.for_each(|row_i| {
+                    let ik = row_i[k];
+                    if ik.is_infinite() {
+                        return;
+                    }
+                    for (ij, &kj) in row_i.iter_mut().zip(row_k) {
+                        let sum = ik.add_weight(kj);
+                        if sum < *ij {
+                            *ij = sum;
+                        }
+                    }
+                });
+            }
+
+            // A negative distance from a node to itself means there is a negative cycle. Stop
+            // right away, otherwise the distances keep decreasing and can overflow
+            if has_negative_diagonal(&distance) {
+                return true;
+            }
+        }
+        false
+    });
+
+    if negative_cycle {
+        return Err(negative_cycle_error(graph));
+    }
+
+    // Return distance and predecessors
+    Ok((distance, predecessors))
+}
+
+/// Struct for the row-parallel Floyd Warshall algorithm on a rayon thread pool
+pub struct FloydWarshallRayon<W>
+where
+    W: Weight + Send + Sync,
+{
+    /// Define the graph, shortest path and the pool to run on
+    pub graph: GraphAM<W>,
+    pub shortest_paths: DistanceMatrix<W>,
+    /// Predecessor matrix, only filled if the paths are tracked
+    pub predecessors: PredecessorMatrix,
+    pub pool: Pool,
+    /// Whether the paths are tracked and the overflow is checked
+    pub config: AlgorithmConfig,
+}
+
+impl<W: Weight + Send + Sync> FloydWarshallRayon<W> {
+    pub fn new(pool: &Pool) -> Self {
+        Self {
+            graph: GraphAM::new(),
+            shortest_paths: Matrix::default(),
+            predecessors: Matrix::default(),
+            pool: pool.clone(),
+            config: AlgorithmConfig::new(),
+        }
+    }
+}
+
+// Define the APSPAlgorithm for the row-parallel FW
+impl<W: Weight + Send + Sync> APSPAlgorithm<W> for FloydWarshallRayon<W> {
+    // Load the graph from a file
+    fn load_graph_from(
+        &mut self,
+        file_path: &str,
+        format: GraphFormat,
+        options: &LoadOptions,
+    ) -> Result<Option<Labels>, LoadError> {
+        load_graph_into(&mut self.graph, file_path, format, options)
+    }
+
+    // Function to execute the FW algorithm
+    fn execute(&mut self) -> Result<(), ExecuteError> {
+        let graph = &self.graph;
+        (self.shortest_paths, self.predecessors) =
+            floyd_warshall_rayon(graph, &self.pool, self.config.track_paths)?;
+
+        self.config.check(&self.shortest_paths)
+    }
+
+    // Put the result in a Hashmap to be able to compare with other programs
+    fn get_result(&mut self) -> APSPResult<W> {
+        self.config.result(&mut self.shortest_paths, &mut self.predecessors)
+    }
+
+    fn config_mut(&mut self) -> &mut AlgorithmConfig {
+        &mut self.config
+    }
+}
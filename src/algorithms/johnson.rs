@@ -0,0 +1,220 @@
+// This is our implementation of Johnson's algorithm
+// It makes it possible to use Dijkstra on graphs with negative edge weights. First Bellman-Ford
+// computes a potential for every node, which is used to reweight all edges to non-negative
+// weights. Then Dijkstra is run on the reweighted graph and the distances are mapped back.
+// 30-01-2024
+
+// Import crates from our files
+use crate::algorithms::bellman_ford::bellman_ford;
+use crate::algorithms::dijkstra_csr::dijkstra_csr;
+use crate::base::{
+    load_graph_into, APSPAlgorithm, APSPResult, AlgorithmConfig, DistanceMatrix, ExecuteError,
+    PredecessorMatrix,
+};
+use crate::graph::{GraphAM, GraphCSR};
+use crate::labels::Labels;
+use crate::loader::{GraphFormat, LoadError, LoadOptions};
+use crate::matrix::Matrix;
+use crate::weight::Weight;
+
+/// Johnson's Algorithm
+/// If track_paths is true the predecessor matrix is computed as well, otherwise it is left empty
+/// Returns an error with the nodes on the cycle if the graph contains a negative cycle
+pub fn johnson<W: Weight>(
+    graph: &GraphAM<W>,
+    track_paths: bool,
+) -> Result<(DistanceMatrix<W>, PredecessorMatrix), ExecuteError> {
+    // Compute the potentials, this fails if there is a negative cycle
+    let csr = GraphCSR::from(graph);
+    let potential = bellman_ford(&csr)?;
+
+    // Without negative edges all potentials are zero and reweighting changes nothing
+    if potential.iter().all(|&h| h == W::zero()) {
+        return Ok(dijkstra_csr(&csr, track_paths));
+    }
+
+    // A reweighted edge or distance can be larger than the weight type holds, even if the real
+    // distance fits. So reweight in i128 for the integer types and f64 for the float types
+    if W::one() / (W::one() + W::one()) == W::zero() {
+        Ok(johnson_reweighted::<W, i128>(&csr, &potential, track_paths))
+    } else {
+        Ok(johnson_reweighted::<W, f64>(&csr, &potential, track_paths))
+    }
+}
+
+// Run Dijkstra on the graph reweighted with the potentials in the wider type R and map the
+// distances back to W. Only the signed and float types get here, which all fit in R
+fn johnson_reweighted<W: Weight, R: Weight>(
+    graph: &GraphCSR<W>,
+    potential: &[W],
+    track_paths: bool,
+) -> (DistanceMatrix<W>, PredecessorMatrix) {
+    let wide = |value: W| R::from(value).expect("the wider type holds every weight");
+    let potential: Vec<R> = potential.iter().map(|&h| wide(h)).collect();
+
+    // Reweight every edge from -> to as weight + h(from) - h(to), which is never negative
+    let mut reweighted = GraphCSR {
+        node_count: graph.node_count,
+        offsets: graph.offsets.clone(),
+        targets: graph.targets.clone(),
+        weights: Vec::with_capacity(graph.weights.len()),
+    };
+    for from in 0..graph.node_count {
+        for (to, weight) in graph.neighbours(from) {
+            let weight = wide(weight).add_weight(potential[from]);
+            let weight = subtract_potential(weight, potential[to]);
+            // With float weights rounding can leave a tiny negative weight, which Dijkstra can't
+            // handle, so clamp it to zero
+            reweighted.weights.push(if weight < R::zero() { R::zero() } else { weight });
+        }
+    }
+
+    // The shortest paths in the reweighted graph are the same as in the original graph
+    let (reweighted_distance, predecessors) = dijkstra_csr(&reweighted, track_paths);
+
+    // Map the distances back to the original weights: d(from, to) + h(to) - h(from). A distance
+    // which does not fit in W, or was already clamped in R, is clamped to SATURATED
+    let n = graph.node_count;
+    let mut distance = Matrix::new(n, n, W::INFINITY);
+    for (from, row) in reweighted_distance.iter_rows().take(n).enumerate() {
+        for (to, &value) in row.iter().enumerate() {
+            if value.is_infinite() {
+                continue;
+            }
+            let value = if value.is_saturated() {
+                None
+            } else {
+                W::from(subtract_potential(value.add_weight(potential[to]), potential[from]))
+            };
+            distance[(from, to)] = value
+                .filter(|value| !value.is_infinite())
+                .unwrap_or(W::SATURATED);
+        }
+    }
+
+    // Return distance and predecessors
+    (distance, predecessors)
+}
+
+// value - potential for a potential which is never positive, clamped to SATURATED like a sum
+// of add_weight. The potential is subtracted on its own, since its negation may not fit
+fn subtract_potential<W: Weight>(value: W, potential: W) -> W {
+    if value > W::MAX_FINITE + potential {
+        W::SATURATED
+    } else {
+        value - potential
+    }
+}
+
+/// Struct for Johnson's algorithm
+pub struct Johnson<W>
+where
+    W: Weight,
+{
+    /// Struct containing the Graph and the Shortest Path
+    pub graph: GraphAM<W>,
+    pub shortest_paths: DistanceMatrix<W>,
+    /// Predecessor matrix, only filled if the paths are tracked
+    pub predecessors: PredecessorMatrix,
+    /// Whether the paths are tracked and the overflow is checked
+    pub config: AlgorithmConfig,
+}
+
+// Implementation to compute a new struct
+impl<W: Weight> Default for Johnson<W> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<W: Weight> Johnson<W> {
+    pub fn new() -> Self {
+        Self {
+            graph: GraphAM::new(),
+            shortest_paths: Matrix::default(),
+            predecessors: Matrix::default(),
+            config: AlgorithmConfig::new(),
+        }
+    }
+}
+
+// Implementation for the APSPAlgorithm struct
+impl<W: Weight> APSPAlgorithm<W> for Johnson<W> {
+    // Load the graph
+    fn load_graph_from(
+        &mut self,
+        file_path: &str,
+        format: GraphFormat,
+        options: &LoadOptions,
+    ) -> Result<Option<Labels>, LoadError> {
+        load_graph_into(&mut self.graph, file_path, format, options)
+    }
+
+    // Excecute the algorithm
+    fn execute(&mut self) -> Result<(), ExecuteError> {
+        let graph = &self.graph;
+        (self.shortest_paths, self.predecessors) = johnson(graph, self.config.track_paths)?;
+
+        self.config.check(&self.shortest_paths)
+    }
+
+    // Write the results to a Hashmap to make comparison easy
+    fn get_result(&mut self) -> APSPResult<W> {
+        self.config.result(&mut self.shortest_paths, &mut self.predecessors)
+    }
+
+    fn config_mut(&mut self) -> &mut AlgorithmConfig {
+        &mut self.config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::base::check_overflow;
+
+    fn graph(nodes: usize, edges: &[(usize, usize, i8)]) -> GraphAM<i8> {
+        let mut graph = GraphAM::with_capacity(nodes);
+        for &(from, to, weight) in edges {
+            graph.add_edge(from, to, weight);
+        }
+        graph
+    }
+
+    #[test]
+    fn saturated_distance_stays_saturated() {
+        // Node 3 gets the potential -50, so the path 0 -> 1 -> 3 of 200 is reweighted to more
+        // than fits in an i8 and must not be mapped back to a finite distance
+        let graph = graph(4, &[(0, 1, 100), (1, 3, 100), (2, 3, -50), (2, 0, 1)]);
+        let (distance, _) = johnson(&graph, false).unwrap();
+        assert_eq!(distance[(2, 3)], -50);
+        assert_eq!(distance[(2, 1)], 101);
+        assert!(distance[(0, 3)].is_saturated());
+        assert!(matches!(check_overflow(&distance), Err(ExecuteError::Overflow { .. })));
+    }
+
+    #[test]
+    fn distance_which_fits_is_not_clamped() {
+        // h(2) = -127 and h(3) = 0, so the reweighted edge 3 -> 2 of 128 does not fit in an i8,
+        // but its distance of 1 does
+        let graph = graph(4, &[(0, 1, -100), (1, 2, -27), (3, 2, 1)]);
+        let (distance, _) = johnson(&graph, false).unwrap();
+        assert_eq!(distance[(0, 2)], -127);
+        assert_eq!(distance[(1, 2)], -27);
+        assert_eq!(distance[(3, 2)], 1);
+        assert!(check_overflow(&distance).is_ok());
+    }
+
+    #[test]
+    fn clamped_negative_cycle_is_found() {
+        // The potentials reach the minimum of an i8 long before the last round
+        let graph = graph(3, &[(0, 1, -100), (1, 0, 50), (1, 2, 1)]);
+        assert_eq!(johnson(&graph, false).unwrap_err(), ExecuteError::NegativeCycle(vec![1, 0]));
+    }
+
+    #[test]
+    fn negative_path_below_the_minimum_overflows() {
+        let graph = graph(3, &[(0, 1, -100), (1, 2, -100)]);
+        assert_eq!(johnson(&graph, false).unwrap_err(), ExecuteError::Overflow { from: 0, to: 2 });
+    }
+}
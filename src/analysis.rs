@@ -2,7 +2,7 @@
 // 30-01-2024
 
 // Function to compute the average. If 0 values then return 0.
-pub fn compute_average(values: &Vec<f64>) -> f64 {
+pub fn compute_average(values: &[f64]) -> f64 {
     let sum: f64 = values.iter().cloned().sum();
     let count = values.len();
 
@@ -13,12 +13,110 @@ pub fn compute_average(values: &Vec<f64>) -> f64 {
     }
 }
 
-// Function to compute the standard deviation
-pub fn compute_std(values: &Vec<f64>, average: f64) -> f64 {
+// Function to compute the sample standard deviation. With less than 2 values there is no spread,
+// so return 0 instead of dividing by zero
+pub fn compute_std(values: &[f64], average: f64) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
     let mut sum_errors_squared = 0.0;
     for &value in values {
         sum_errors_squared += (value - average).powi(2);
     }
     let mean_squared_error = sum_errors_squared / (values.len() - 1) as f64;
     mean_squared_error.sqrt()
-}
\ No newline at end of file
+}
+
+// Function to compute the p-th percentile (0 to 100), interpolated between the two closest values.
+// If 0 values then return 0.
+pub fn compute_percentile(values: &[f64], percentile: f64) -> f64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let count = sorted.len();
+
+    if count == 0 {
+        return 0.0;
+    }
+    let rank = percentile.clamp(0.0, 100.0) / 100.0 * (count - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+}
+
+// Function to compute the median, the average of the two middle values for an even count
+pub fn compute_median(values: &[f64]) -> f64 {
+    compute_percentile(values, 50.0)
+}
+
+// The 97.5% quantile of the Student t-distribution, for a two-sided 95% confidence interval
+fn t_quantile(degrees_of_freedom: usize) -> f64 {
+    const TABLE: [f64; 30] = [
+        12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228, 2.201, 2.179, 2.160,
+        2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086, 2.080, 2.074, 2.069, 2.064, 2.060, 2.056,
+        2.052, 2.048, 2.045, 2.042,
+    ];
+    match degrees_of_freedom {
+        0 => f64::INFINITY,
+        1..=30 => TABLE[degrees_of_freedom - 1],
+        31..=40 => 2.021,
+        41..=60 => 2.000,
+        61..=120 => 1.980,
+        _ => 1.960,
+    }
+}
+
+// Function to compute the 95% confidence interval of the average. With less than 2 values the
+// interval is only the average
+pub fn compute_confidence_interval(values: &[f64], average: f64, std: f64) -> (f64, f64) {
+    if values.len() < 2 {
+        return (average, average);
+    }
+    let half_width = t_quantile(values.len() - 1) * std / (values.len() as f64).sqrt();
+    (average - half_width, average + half_width)
+}
+
+// Function to count the outliers, the values more than 1.5 times the interquartile range below the
+// first or above the third quartile
+pub fn count_outliers(values: &[f64]) -> usize {
+    let first_quartile = compute_percentile(values, 25.0);
+    let third_quartile = compute_percentile(values, 75.0);
+    let fence = 1.5 * (third_quartile - first_quartile);
+    values
+        .iter()
+        .filter(|&&value| value < first_quartile - fence || value > third_quartile + fence)
+        .count()
+}
+
+// The statistics of the execution times of one algorithm
+pub struct Summary {
+    pub min: f64,
+    pub p5: f64,
+    pub median: f64,
+    pub p95: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub std: f64,
+    // The 95% confidence interval of the mean
+    pub ci_low: f64,
+    pub ci_high: f64,
+    pub outliers: usize,
+}
+
+// Function to compute all statistics at once. If 0 values then everything is 0.
+pub fn summarize(values: &[f64]) -> Summary {
+    let mean = compute_average(values);
+    let std = compute_std(values, mean);
+    let (ci_low, ci_high) = compute_confidence_interval(values, mean, std);
+    Summary {
+        min: compute_percentile(values, 0.0),
+        p5: compute_percentile(values, 5.0),
+        median: compute_median(values),
+        p95: compute_percentile(values, 95.0),
+        max: compute_percentile(values, 100.0),
+        mean,
+        std,
+        ci_low,
+        ci_high,
+        outliers: count_outliers(values),
+    }
+}
@@ -1,73 +1,360 @@
 use std::collections::HashMap;
-use std::fmt::Debug;
+use std::fmt::{self, Debug, Display};
+use std::mem;
 
-use num::Num;
+use crate::labels::Labels;
+use crate::loader::{FromGraphFile, GraphFormat, LoadError, LoadOptions};
+use crate::matrix::Matrix;
+use crate::weight::Weight;
 
-#[derive(Debug)]
+/// Distance matrix used by the algorithms, [`Weight::INFINITY`] means that there is no (known) path
+pub type DistanceMatrix<W> = Matrix<W>;
+/// `predecessors[(i, j)]` is the node before j on the shortest path from i to j
+pub type PredecessorMatrix = Matrix<Option<usize>>;
+
+/// The distances between all pairs of nodes computed by an [`APSPAlgorithm`], stored in the same
+/// dense matrix as the algorithms use. Pairs without a path have the distance INFINITY
+#[derive(Clone, Debug)]
 pub struct APSPResult<W>
 where
-    W: Num + Copy + Debug,
+    W: Weight,
 {
-    pub shortest_paths: HashMap<(usize, usize), W>,
+    /// `shortest_paths[(i, j)]` is the distance from i to j
+    pub shortest_paths: DistanceMatrix<W>,
+    /// Predecessor matrix, `predecessors[(i, j)]` is the node before j on the shortest path from i to j.
+    /// Only filled if the algorithm was asked to track the paths
+    pub predecessors: Option<PredecessorMatrix>,
 }
 
-impl<W: Num + Copy + Debug> APSPResult<W> {
-    pub fn new() -> Self {
+impl<W: Weight> Default for APSPResult<W> {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl<W: Weight> APSPResult<W> {
+    /// Create a result for node_count nodes without any paths
+    pub fn new(node_count: usize) -> Self {
         APSPResult {
-            shortest_paths: HashMap::new(),
+            shortest_paths: Matrix::new(node_count, node_count, W::INFINITY),
+            predecessors: None,
         }
     }
 
+    /// Create a result from the distance matrix and optionally the predecessor matrix
+    pub fn from_matrix(shortest_paths: DistanceMatrix<W>, predecessors: Option<PredecessorMatrix>) -> Self {
+        APSPResult {
+            shortest_paths,
+            predecessors,
+        }
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.shortest_paths.rows()
+    }
+
+    /// Set the distance from `from` to `to`
     pub fn add(&mut self, from: usize, to: usize, cost: W) {
-        self.shortest_paths.insert((from, to), cost);
-    }
-
-
-    pub fn result_compare(&self, result2: &HashMap<(usize, usize), W>) -> bool {
-        let mut returnvalue = true;
-     
-        for (key, cost1) in &self.shortest_paths {
-            match result2.get(key) {
-                Some(cost2) => {
-                    if cost1 != cost2 {
-                        println!("{:?} {:?} {:?}", key, cost1, cost2);
-                        returnvalue = false;
-                    }
-                    
-
-                }
-                None => {
-                    // If on the main diagional skip, this is always zero
-                    if key.0 == key.1 {
-                        continue;
-                    }
-
-                    
-                    returnvalue = false; // Key is missing in result2
-                }
+        self.shortest_paths[(from, to)] = cost;
+    }
+
+    /// The distance from `from` to `to`, None if there is no path or if a node is not in the graph
+    pub fn get(&self, from: usize, to: usize) -> Option<W> {
+        let n = self.node_count();
+        if from >= n || to >= n {
+            return None;
+        }
+        self.shortest_paths[(from, to)].finite()
+    }
+
+    /// The distances from `from` to all nodes, INFINITY if there is no path
+    pub fn row(&self, from: usize) -> &[W] {
+        self.shortest_paths.row(from)
+    }
+
+    /// The distances from all nodes to `to`, INFINITY if there is no path
+    pub fn column(&self, to: usize) -> impl Iterator<Item = W> + '_ {
+        self.shortest_paths.iter_rows().take(self.node_count()).map(move |row| row[to])
+    }
+
+    /// Iterate over all pairs with a path, together with their distance
+    pub fn reachable_pairs(&self) -> impl Iterator<Item = ((usize, usize), W)> + '_ {
+        self.shortest_paths
+            .iter_rows()
+            .take(self.node_count())
+            .enumerate()
+            .flat_map(|(i, row)| {
+                row.iter()
+                    .enumerate()
+                    .filter_map(move |(j, value)| value.finite().map(|value| ((i, j), value)))
+            })
+    }
+
+    /// The distances of all pairs with a path in a HashMap, keyed by (from, to)
+    pub fn to_hashmap(&self) -> HashMap<(usize, usize), W> {
+        self.reachable_pairs().collect()
+    }
+
+    /// Return the sequence of nodes on the shortest path from `from` to `to` (both included).
+    /// Returns None if there is no path or if the predecessors were not tracked
+    pub fn path(&self, from: usize, to: usize) -> Option<Vec<usize>> {
+        let predecessors = self.predecessors.as_ref()?;
+        reconstruct_path(predecessors, from, to)
+    }
+}
+
+/// Walk back over the predecessor matrix to build the path from `from` to `to`
+pub fn reconstruct_path(
+    predecessors: &PredecessorMatrix,
+    from: usize,
+    to: usize,
+) -> Option<Vec<usize>> {
+    if from == to {
+        return Some(vec![from]);
+    }
+    if from >= predecessors.rows() {
+        return None;
+    }
+
+    path_from_row(predecessors.row(from), from, to)
+}
+
+/// Same as [`reconstruct_path`], but for a single row with the predecessors on the paths from
+/// `from`. Used when the rows of the matrix are not indexed by node, e.g. for a query
+pub fn path_from_row(row: &[Option<usize>], from: usize, to: usize) -> Option<Vec<usize>> {
+    let mut path = vec![to];
+    let mut current = to;
+
+    while current != from {
+        current = (*row.get(current)?)?;
+        path.push(current);
+
+        // A path can never be longer than the number of nodes, if it is we are walking in a cycle
+        if path.len() > row.len() {
+            return None;
+        }
+    }
+
+    path.reverse();
+    Some(path)
+}
+
+/// Build the starting predecessor matrix: i is the predecessor of j if there is an edge i -> j
+pub fn initial_predecessors<W: Copy>(
+    adjacency_matrix: &[Vec<Option<W>>],
+) -> PredecessorMatrix {
+    let n = adjacency_matrix.len();
+    let mut predecessors = Matrix::new(n, n, None);
+    for (i, row) in adjacency_matrix.iter().enumerate() {
+        for (j, weight) in row.iter().enumerate() {
+            if weight.is_some() && i != j {
+                predecessors[(i, j)] = Some(i);
+            }
+        }
+    }
+    predecessors
+}
+
+/// Errors an algorithm can run into while computing the shortest paths
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExecuteError {
+    /// The graph contains a cycle with a negative total weight, the nodes on the cycle are given
+    /// in the order of the cycle. Empty if the algorithm can not tell which nodes are on it
+    NegativeCycle(Vec<usize>),
+    /// The algorithm only works for non-negative weights, but the edge from -> to is negative
+    NegativeEdge { from: usize, to: usize },
+    /// The distance from -> to does not fit in the weight type. Only reported if the overflow
+    /// check of the algorithm is turned on
+    Overflow { from: usize, to: usize },
+    /// A node that was asked for, e.g. as the source of a query, is not in the graph
+    InvalidNode { node: usize, node_count: usize },
+}
+
+impl Display for ExecuteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExecuteError::NegativeCycle(nodes) if nodes.is_empty() => {
+                write!(f, "the graph contains a negative cycle")
+            }
+            ExecuteError::NegativeCycle(nodes) => {
+                write!(f, "the graph contains a negative cycle through nodes {:?}", nodes)
+            }
+            ExecuteError::NegativeEdge { from, to } => write!(
+                f,
+                "the edge {} -> {} has a negative weight, use Johnson's algorithm instead",
+                from, to
+            ),
+            ExecuteError::Overflow { from, to } => write!(
+                f,
+                "the distance from {} to {} does not fit in the weight type, use a larger type",
+                from, to
+            ),
+            ExecuteError::InvalidNode { node, node_count } => {
+                write!(f, "node {} is not in the graph with {} nodes", node, node_count)
             }
         }
-        returnvalue
+    }
+}
+
+impl std::error::Error for ExecuteError {}
+
+/// Set the main diagonal to zero such that the distance to the original point is equal 0.
+/// A negative self loop is kept, since that is a negative cycle on its own
+pub fn zero_diagonal<W: Weight>(distance: &mut DistanceMatrix<W>) {
+    for i in 0..distance.rows() {
+        if distance[(i, i)] < W::zero() {
+            continue;
+        }
+        distance[(i, i)] = W::zero();
+    }
+}
+
+/// Check if a node has a negative distance to itself, which means that there is a negative cycle
+pub fn has_negative_diagonal<W: Weight>(distance: &DistanceMatrix<W>) -> bool {
+    (0..distance.rows()).any(|i| distance[(i, i)] < W::zero())
+}
+
+/// Report the first pair whose distance was clamped because it does not fit in the weight type
+pub fn check_overflow<W: Weight>(distance: &DistanceMatrix<W>) -> Result<(), ExecuteError> {
+    let cols = distance.cols();
+    match distance.as_slice().iter().position(|value| value.is_saturated()) {
+        Some(index) => Err(ExecuteError::Overflow {
+            from: index / cols,
+            to: index % cols,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Build the result from the matrices of an algorithm, unreachable pairs keep the distance
+/// INFINITY. The matrices are moved into the result instead of copied and are left empty
+pub fn result_from_matrix<W: Weight>(
+    shortest_paths: &mut DistanceMatrix<W>,
+    predecessors: Option<&mut PredecessorMatrix>,
+) -> APSPResult<W> {
+    APSPResult::from_matrix(mem::take(shortest_paths), predecessors.map(mem::take))
+}
+
+/// The settings shared by the algorithms, set with [`APSPAlgorithm::with_paths`] and
+/// [`APSPAlgorithm::with_overflow_check`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AlgorithmConfig {
+    /// Also compute the predecessor matrix such that the paths can be reconstructed
+    pub track_paths: bool,
+    /// If true, execute fails when a distance does not fit in the weight type
+    pub overflow_check: bool,
+}
+
+impl AlgorithmConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Report the first pair whose distance does not fit in the weight type if the overflow is
+    /// checked, see [`check_overflow`]
+    pub fn check<W: Weight>(&self, distance: &DistanceMatrix<W>) -> Result<(), ExecuteError> {
+        if self.overflow_check {
+            check_overflow(distance)
+        } else {
+            Ok(())
+        }
     }
 
-    // Uncommented if you want to use
-    // pub fn print_result(&self) {
-    //     for key in &self.shortest_paths {
-    //         println!("{:?}", key);
-    //     }
-    // }
+    /// Move the matrices of the last execution into a result, the predecessors only if the paths
+    /// are tracked, see [`result_from_matrix`]
+    pub fn result<W: Weight>(
+        &self,
+        shortest_paths: &mut DistanceMatrix<W>,
+        predecessors: &mut PredecessorMatrix,
+    ) -> APSPResult<W> {
+        let predecessors = if self.track_paths {
+            Some(predecessors)
+        } else {
+            None
+        };
+        result_from_matrix(shortest_paths, predecessors)
+    }
+}
 
-    // Uncommented if you want to use
-    // pub fn print_result_key(to_print: &HashMap<(usize, usize), W>, &key: &(usize, usize)) {
-    //     println!("{:?}", to_print.get(&key));
-    // }
+/// Load a graph of any type into the graph of an algorithm and return the labels of a labelled
+/// file, see [`APSPAlgorithm::load_graph_from`]
+pub fn load_graph_into<W: Weight, G: FromGraphFile<W>>(
+    graph: &mut G,
+    file_path: &str,
+    format: GraphFormat,
+    options: &LoadOptions,
+) -> Result<Option<Labels>, LoadError> {
+    let file = G::from_graph_file(file_path, format, options)?;
+    *graph = file.graph;
+    Ok(file.labels)
 }
 
+/// The interface shared by all APSP algorithms
 pub trait APSPAlgorithm<W>
 where
-    W: Num + Copy + Debug,
+    W: Weight,
 {
-    fn execute(&mut self);
-    fn load_graph(&mut self, file_path: &str, is_sparse_format: bool);
+    /// Compute the shortest paths of the loaded graph
+    fn execute(&mut self) -> Result<(), ExecuteError>;
+    /// Load the graph from a file in the sparse (edge list) or the dense (matrix) format
+    fn load_graph(&mut self, file_path: &str, is_sparse_format: bool) -> Result<(), LoadError> {
+        self.load_graph_with(file_path, is_sparse_format, &LoadOptions::default())
+    }
+    /// Same as [`APSPAlgorithm::load_graph`], with the options for the sparse format, e.g. to
+    /// load the edges as directed
+    fn load_graph_with(
+        &mut self,
+        file_path: &str,
+        is_sparse_format: bool,
+        options: &LoadOptions,
+    ) -> Result<(), LoadError> {
+        let format = if is_sparse_format {
+            GraphFormat::Sparse
+        } else {
+            GraphFormat::Dense
+        };
+        self.load_graph_from(file_path, format, options).map(|_| ())
+    }
+    /// Load the graph from a file in any of the supported formats, e.g. a DIMACS or Matrix
+    /// Market file of a public dataset. Returns the labels of the nodes of a labelled file, see
+    /// [`FromGraphFile::from_graph_file`](crate::loader::FromGraphFile::from_graph_file)
+    fn load_graph_from(
+        &mut self,
+        file_path: &str,
+        format: GraphFormat,
+        options: &LoadOptions,
+    ) -> Result<Option<Labels>, LoadError>;
+    /// Load a graph in the sparse format with labels instead of node ids, and return the labels
+    /// of the nodes such that the result can be used by label
+    fn load_labelled_graph(
+        &mut self,
+        file_path: &str,
+        options: &LoadOptions,
+    ) -> Result<Labels, LoadError> {
+        let options = options.labelled(true);
+        let labels = self.load_graph_from(file_path, GraphFormat::Sparse, &options)?;
+        Ok(labels.unwrap_or_default())
+    }
+    /// Collect the distances of the last execution. They are moved into the result, so it can
+    /// only be collected once per execution
     fn get_result(&mut self) -> APSPResult<W>;
+    /// The settings of the algorithm, see [`AlgorithmConfig`]
+    fn config_mut(&mut self) -> &mut AlgorithmConfig;
+    /// Also compute the predecessor matrix such that the paths can be reconstructed
+    fn with_paths(mut self) -> Self
+    where
+        Self: Sized,
+    {
+        self.config_mut().track_paths = true;
+        self
+    }
+    /// Report the pair whose distance does not fit in the weight type instead of returning the
+    /// clamped distances
+    fn with_overflow_check(mut self) -> Self
+    where
+        Self: Sized,
+    {
+        self.config_mut().overflow_check = true;
+        self
+    }
 }
@@ -0,0 +1,419 @@
+// The command line interface of the analysis binary
+// The arguments are parsed with clap, `apsp --help` lists the subcommands and their flags
+// 30-01-2024
+
+// Import crates
+use clap::builder::RangedU64ValueParser;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+// Import crates from the library
+use apsp::{GraphFormat, LoadOptions, Model, WeightDistribution};
+
+/// Run and benchmark the APSP algorithms on a graph file
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Compare the results of the algorithms with the Floyd-Warshall of petgraph
+    Eval(EvalArgs),
+    /// Measure the runtime of the algorithms
+    Bench(BenchArgs),
+    /// Evaluate and then measure the algorithms
+    All(AllArgs),
+    /// Generate a random graph and write it to a file
+    Generate(GenerateArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct EvalArgs {
+    #[command(flatten)]
+    pub run: RunArgs,
+    #[command(flatten)]
+    pub eval: EvalOptions,
+}
+
+#[derive(Args, Debug)]
+pub struct BenchArgs {
+    #[command(flatten)]
+    pub run: RunArgs,
+    #[command(flatten)]
+    pub bench: BenchOptions,
+}
+
+#[derive(Args, Debug)]
+pub struct AllArgs {
+    #[command(flatten)]
+    pub run: RunArgs,
+    #[command(flatten)]
+    pub eval: EvalOptions,
+    #[command(flatten)]
+    pub bench: BenchOptions,
+}
+
+/// The graph, the algorithms and their parameters, shared by all subcommands
+#[derive(Args, Debug)]
+pub struct RunArgs {
+    /// The graph file, relative paths are relative to the current directory
+    pub file: String,
+    /// The format of the graph file, taken from the extension for the standard formats (.gr,
+    /// .mtx, .graphml and .csv) and sparse otherwise if not given
+    #[arg(long, value_enum)]
+    pub format: Option<Format>,
+    /// Load the edges as directed, overrides the header of a sparse file and the defaults of the
+    /// other formats
+    #[arg(long, conflicts_with = "undirected")]
+    pub directed: bool,
+    /// Add every edge in both directions, overrides the header of a sparse file
+    #[arg(long)]
+    pub undirected: bool,
+    /// The node ids in the file start at 1, overrides the header of a sparse file or a CSV file
+    #[arg(long)]
+    pub one_based: bool,
+    /// The nodes in the file are labels instead of numbers, for a sparse or a CSV file
+    #[arg(long)]
+    pub labelled: bool,
+    /// The type of the edge weights
+    #[arg(long, value_enum, default_value_t = WeightKind::U16)]
+    pub weight: WeightKind,
+    /// The algorithms to run, all of them if not given
+    #[arg(long, short, value_enum, value_delimiter = ',')]
+    pub algorithms: Vec<Algorithm>,
+    /// The number of threads of the parallel algorithms
+    #[arg(long, short, default_value_t = 10, value_parser = at_least_one())]
+    pub threads: usize,
+    /// The block size of the parallel blocked Floyd-Warshall algorithms
+    #[arg(long, default_value_t = 64, value_parser = at_least_one())]
+    pub block_size: usize,
+    /// The number of blocks per row of the sequential blocked Floyd-Warshall
+    #[arg(long, default_value_t = 10, value_parser = at_least_one())]
+    pub blocks: usize,
+}
+
+// The thread and block counts are at least one, a zero would divide by zero or never finish. So
+// are the iteration counts, the statistics of zero measurements are meaningless
+fn at_least_one() -> RangedU64ValueParser<usize> {
+    RangedU64ValueParser::<usize>::new().range(1..)
+}
+
+#[derive(Args, Debug)]
+pub struct EvalOptions {
+    /// The largest difference allowed between two distances, 0 for the integer types, 1e-3 for
+    /// f32 and 1e-9 for f64 if not given
+    #[arg(long)]
+    pub tolerance: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct BenchOptions {
+    /// The number of measured executions per algorithm, the minimum if --precision is given
+    #[arg(long, short, default_value_t = 10, value_parser = at_least_one())]
+    pub iterations: usize,
+    /// The number of executions before the measurement starts, to warm up the caches
+    #[arg(long, default_value_t = 1)]
+    pub warmup: usize,
+    /// Keep measuring until the 95% confidence interval of the mean is within this fraction of
+    /// the mean, e.g. 0.02 for 2%
+    #[arg(long)]
+    pub precision: Option<f64>,
+    /// The largest number of measured executions with --precision
+    #[arg(long, default_value_t = 100, value_parser = at_least_one())]
+    pub max_iterations: usize,
+    /// Append a record with the statistics of every algorithm to this file
+    #[arg(long, short)]
+    pub output: Option<String>,
+    /// The format of the output file, JSON for a .json or .jsonl file and CSV otherwise if not
+    /// given
+    #[arg(long, value_enum)]
+    pub output_format: Option<OutputFormat>,
+}
+
+#[derive(Args, Debug)]
+pub struct GenerateArgs {
+    #[command(subcommand)]
+    pub model: ModelArgs,
+}
+
+/// The random graph models, with their parameters
+#[derive(Subcommand, Debug)]
+pub enum ModelArgs {
+    /// Erdős–Rényi graph where every pair of nodes is connected with the same probability
+    Gnp {
+        /// The number of nodes
+        #[arg(long, short)]
+        nodes: usize,
+        /// The probability of an edge between two nodes
+        #[arg(long, short)]
+        probability: f64,
+        #[command(flatten)]
+        options: GenerateOptions,
+    },
+    /// Erdős–Rényi graph with exactly the given number of edges
+    Gnm {
+        /// The number of nodes
+        #[arg(long, short)]
+        nodes: usize,
+        /// The number of edges, arcs for a directed graph
+        #[arg(long, short)]
+        edges: usize,
+        #[command(flatten)]
+        options: GenerateOptions,
+    },
+    /// Random geometric graph of points in the unit square, connected if they are close
+    Geometric {
+        /// The number of nodes
+        #[arg(long, short)]
+        nodes: usize,
+        /// The largest distance between two connected points
+        #[arg(long, short)]
+        radius: f64,
+        #[command(flatten)]
+        options: GenerateOptions,
+    },
+    /// Barabási–Albert graph where every new node attaches to the well connected nodes
+    BarabasiAlbert {
+        /// The number of nodes
+        #[arg(long, short)]
+        nodes: usize,
+        /// The number of edges of every new node
+        #[arg(long, short)]
+        edges_per_node: usize,
+        #[command(flatten)]
+        options: GenerateOptions,
+    },
+    /// Grid graph, road-like if only a part of the streets is kept
+    Grid {
+        /// The number of rows
+        #[arg(long, short)]
+        rows: usize,
+        /// The number of columns
+        #[arg(long, short)]
+        columns: usize,
+        /// The probability that a street between two neighbouring nodes is kept
+        #[arg(long, short, default_value_t = 1.0)]
+        keep: f64,
+        #[command(flatten)]
+        options: GenerateOptions,
+    },
+    /// Complete graph with an edge between every pair of nodes
+    Complete {
+        /// The number of nodes
+        #[arg(long, short)]
+        nodes: usize,
+        #[command(flatten)]
+        options: GenerateOptions,
+    },
+}
+
+/// The output file and the options shared by all models
+#[derive(Args, Debug)]
+pub struct GenerateOptions {
+    /// The file to write the graph to
+    pub output: String,
+    /// The format of the file, taken from the extension for the standard formats (.gr, .mtx,
+    /// .graphml and .csv) and sparse otherwise if not given
+    #[arg(long, value_enum)]
+    pub format: Option<Format>,
+    /// Generate a directed graph, with an independent weight in each direction
+    #[arg(long)]
+    pub directed: bool,
+    /// The seed of the random number generator, the same seed gives the same graph
+    #[arg(long, short, default_value_t = 0)]
+    pub seed: u64,
+    /// The distribution of the weights: constant:<w>, uniform:<low>:<high>, normal:<mean>:<std>
+    /// or exponential:<mean>, rounded for the integer types
+    #[arg(long, short, default_value = "uniform:0:100")]
+    pub weights: WeightDistribution,
+    /// The type of the edge weights
+    #[arg(long, value_enum, default_value_t = WeightKind::U16)]
+    pub weight: WeightKind,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Comma separated values with a header line
+    Csv,
+    /// One JSON object per line
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// One edge per line
+    Sparse,
+    /// The full adjacency matrix
+    Dense,
+    /// The DIMACS shortest path format (.gr)
+    Dimacs,
+    /// A Matrix Market coordinate matrix (.mtx)
+    MatrixMarket,
+    /// GraphML, the nodes are labelled by their ids
+    Graphml,
+    /// An edge list with the columns from,to[,weight]
+    Csv,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WeightKind {
+    U16,
+    U32,
+    I32,
+    F32,
+    F64,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Algorithm {
+    /// The Floyd-Warshall of petgraph, only measured by bench, eval uses it as the reference
+    Petgraph,
+    FloydWarshall,
+    Dijkstra,
+    DijkstraPar,
+    FloydWarshallBlock,
+    FloydWarshallBlockPar,
+    Johnson,
+    DijkstraCsr,
+    #[cfg(feature = "rayon")]
+    DijkstraRayon,
+    #[cfg(feature = "rayon")]
+    FloydWarshallRayon,
+    #[cfg(feature = "rayon")]
+    FloydWarshallBlockRayon,
+}
+
+impl Algorithm {
+    /// The name used in the output, the same as the type name
+    pub fn name(self) -> &'static str {
+        match self {
+            Algorithm::Petgraph => "BaseLineFloydWarshall",
+            Algorithm::FloydWarshall => "FloydWarshall",
+            Algorithm::Dijkstra => "Dijkstra",
+            Algorithm::DijkstraPar => "DijkstraPar",
+            Algorithm::FloydWarshallBlock => "FloydWarshallBlock",
+            Algorithm::FloydWarshallBlockPar => "FloydWarshallBlockPar",
+            Algorithm::Johnson => "Johnson",
+            Algorithm::DijkstraCsr => "DijkstraCSR",
+            #[cfg(feature = "rayon")]
+            Algorithm::DijkstraRayon => "DijkstraRayon",
+            #[cfg(feature = "rayon")]
+            Algorithm::FloydWarshallRayon => "FloydWarshallRayon",
+            #[cfg(feature = "rayon")]
+            Algorithm::FloydWarshallBlockRayon => "FloydWarshallBlockRayon",
+        }
+    }
+
+    /// True if the algorithm runs on `--threads` threads
+    pub fn uses_threads(self) -> bool {
+        match self {
+            Algorithm::DijkstraPar | Algorithm::FloydWarshallBlockPar => true,
+            #[cfg(feature = "rayon")]
+            Algorithm::DijkstraRayon
+            | Algorithm::FloydWarshallRayon
+            | Algorithm::FloydWarshallBlockRayon => true,
+            _ => false,
+        }
+    }
+
+    /// True if the algorithm uses `--block-size`
+    pub fn uses_block_size(self) -> bool {
+        match self {
+            Algorithm::FloydWarshallBlockPar => true,
+            #[cfg(feature = "rayon")]
+            Algorithm::FloydWarshallBlockRayon => true,
+            _ => false,
+        }
+    }
+
+    /// True if the algorithm uses `--blocks`
+    pub fn uses_blocks(self) -> bool {
+        self == Algorithm::FloydWarshallBlock
+    }
+}
+
+impl WeightKind {
+    /// The name of the type, as given on the command line
+    pub fn name(self) -> &'static str {
+        match self {
+            WeightKind::U16 => "u16",
+            WeightKind::U32 => "u32",
+            WeightKind::I32 => "i32",
+            WeightKind::F32 => "f32",
+            WeightKind::F64 => "f64",
+        }
+    }
+}
+
+impl Format {
+    /// The format of a file, `format` if given and else taken from the extension
+    pub fn graph_format(format: Option<Format>, file: &str) -> GraphFormat {
+        match format {
+            Some(Format::Sparse) => GraphFormat::Sparse,
+            Some(Format::Dense) => GraphFormat::Dense,
+            Some(Format::Dimacs) => GraphFormat::Dimacs,
+            Some(Format::MatrixMarket) => GraphFormat::MatrixMarket,
+            Some(Format::Graphml) => GraphFormat::GraphML,
+            Some(Format::Csv) => GraphFormat::Csv,
+            None => GraphFormat::from_extension(file).unwrap_or(GraphFormat::Sparse),
+        }
+    }
+}
+
+impl RunArgs {
+    /// The options for loading the graph, the header of the file decides what is not given
+    pub fn load_options(&self) -> LoadOptions {
+        let mut options = LoadOptions::new();
+        if self.directed || self.undirected {
+            options = options.directed(self.directed);
+        }
+        if self.one_based {
+            options = options.one_based(true);
+        }
+        if self.labelled {
+            options = options.labelled(true);
+        }
+        options
+    }
+
+    /// The format of the graph file, see `--format`
+    pub fn graph_format(&self) -> GraphFormat {
+        Format::graph_format(self.format, &self.file)
+    }
+
+    /// The selected algorithms, all of them if none were given
+    pub fn selected_algorithms(&self) -> Vec<Algorithm> {
+        if self.algorithms.is_empty() {
+            Algorithm::value_variants().to_vec()
+        } else {
+            self.algorithms.clone()
+        }
+    }
+}
+
+impl ModelArgs {
+    /// The model with its parameters and the shared options
+    pub fn split(&self) -> (Model, &GenerateOptions) {
+        match self {
+            ModelArgs::Gnp { nodes, probability, options } => {
+                (Model::Gnp { nodes: *nodes, probability: *probability }, options)
+            }
+            ModelArgs::Gnm { nodes, edges, options } => {
+                (Model::Gnm { nodes: *nodes, edges: *edges }, options)
+            }
+            ModelArgs::Geometric { nodes, radius, options } => {
+                (Model::Geometric { nodes: *nodes, radius: *radius }, options)
+            }
+            ModelArgs::BarabasiAlbert { nodes, edges_per_node, options } => (
+                Model::BarabasiAlbert { nodes: *nodes, edges_per_node: *edges_per_node },
+                options,
+            ),
+            ModelArgs::Grid { rows, columns, keep, options } => {
+                (Model::Grid { rows: *rows, columns: *columns, keep: *keep }, options)
+            }
+            ModelArgs::Complete { nodes, options } => (Model::Complete { nodes: *nodes }, options),
+        }
+    }
+}
@@ -0,0 +1,225 @@
+// Comparing the results of two algorithms pair by pair
+// Instead of a single true/false the comparison counts what differs, keeps the largest
+// difference and a few of the offending pairs, such that a failing run can be looked into
+// 30-01-2024
+
+// Import crates
+use std::fmt::{self, Display};
+
+// Import crates from our files
+use crate::base::APSPResult;
+use crate::weight::Weight;
+
+/// The number of differing pairs kept in a [`ComparisonReport`]
+pub const MAX_SAMPLES: usize = 10;
+
+/// A pair whose distance differs between the two results, None means that there is no path
+#[derive(Clone, Debug, PartialEq)]
+pub struct PairDifference<W> {
+    pub from: usize,
+    pub to: usize,
+    pub left: Option<W>,
+    pub right: Option<W>,
+}
+
+/// The outcome of [`APSPResult::compare`], `left` is the result compare was called on and
+/// `right` the one it was compared with
+#[derive(Clone, Debug)]
+pub struct ComparisonReport<W> {
+    pub tolerance: W,
+    /// Pairs with a distance in both results
+    pub compared: usize,
+    /// Pairs with a distance in both results which differ more than the tolerance
+    pub mismatched: usize,
+    /// Pairs with a distance in right but not in left
+    pub missing_left: usize,
+    /// Pairs with a distance in left but not in right
+    pub missing_right: usize,
+    /// The largest absolute difference over the compared pairs
+    pub max_absolute_difference: f64,
+    /// The largest difference relative to the larger of the two distances
+    pub max_relative_difference: f64,
+    /// The first [`MAX_SAMPLES`] pairs which are mismatched or missing on one side
+    pub samples: Vec<PairDifference<W>>,
+}
+
+impl<W: Weight> ComparisonReport<W> {
+    /// The number of pairs which are mismatched or missing on one side
+    pub fn differences(&self) -> usize {
+        self.mismatched + self.missing_left + self.missing_right
+    }
+
+    /// True if all distances agree within the tolerance and both have the same pairs
+    pub fn is_equal(&self) -> bool {
+        self.differences() == 0
+    }
+}
+
+impl<W: Weight> Display for ComparisonReport<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} pairs compared, {} mismatched, {} missing on the left, {} missing on the right, \
+             largest difference {} (relative {:e})",
+            self.compared,
+            self.mismatched,
+            self.missing_left,
+            self.missing_right,
+            self.max_absolute_difference,
+            self.max_relative_difference
+        )?;
+        for sample in &self.samples {
+            write!(
+                f,
+                "\n  {} -> {}: {:?} vs {:?}",
+                sample.from, sample.to, sample.left, sample.right
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// The weights are compared in f64, such that the difference of two large signed values can't
+// overflow. All primitive types can be converted
+fn to_f64<W: Weight>(value: W) -> f64 {
+    value.to_f64().unwrap_or(f64::NAN)
+}
+
+impl<W: Weight> APSPResult<W> {
+    /// Compare every pair with another result. Distances may differ at most `tolerance`, use zero
+    /// for the integer types. Float distances can differ a little because the algorithms add up
+    /// the weights in a different order
+    pub fn compare(&self, other: &APSPResult<W>, tolerance: W) -> ComparisonReport<W> {
+        let mut report = ComparisonReport {
+            tolerance,
+            compared: 0,
+            mismatched: 0,
+            missing_left: 0,
+            missing_right: 0,
+            max_absolute_difference: 0.0,
+            max_relative_difference: 0.0,
+            samples: Vec::new(),
+        };
+
+        // Pairs outside of the smaller result count as missing there
+        let n = self.node_count().max(other.node_count());
+        for from in 0..n {
+            for to in 0..n {
+                let left = self.get(from, to);
+                let right = other.get(from, to);
+                let differs = match (left, right) {
+                    (Some(cost1), Some(cost2)) => {
+                        report.compared += 1;
+                        let (value1, value2) = (to_f64(cost1), to_f64(cost2));
+                        let difference = (value1 - value2).abs();
+                        let scale = value1.abs().max(value2.abs());
+                        report.max_absolute_difference =
+                            report.max_absolute_difference.max(difference);
+                        if scale > 0.0 {
+                            report.max_relative_difference =
+                                report.max_relative_difference.max(difference / scale);
+                        }
+
+                        // Written without a subtraction, which could overflow for the integer types
+                        let too_small = cost1.add_weight(tolerance) < cost2;
+                        let too_large = cost2.add_weight(tolerance) < cost1;
+                        if too_small || too_large {
+                            report.mismatched += 1;
+                        }
+                        too_small || too_large
+                    }
+                    (None, Some(_)) => {
+                        report.missing_left += 1;
+                        true
+                    }
+                    (Some(_), None) => {
+                        report.missing_right += 1;
+                        true
+                    }
+                    (None, None) => false,
+                };
+
+                if differs && report.samples.len() < MAX_SAMPLES {
+                    report.samples.push(PairDifference {
+                        from,
+                        to,
+                        left,
+                        right,
+                    });
+                }
+            }
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matrix::Matrix;
+
+    #[test]
+    fn float_distances_are_equal_within_the_tolerance() {
+        let mut left = APSPResult::<f64>::new(3);
+        let mut right = APSPResult::<f64>::new(3);
+        left.add(0, 1, 0.3);
+        right.add(0, 1, 0.1 + 0.2);
+        left.add(1, 2, -2.0);
+        right.add(1, 2, -2.0);
+
+        let report = left.compare(&right, 1e-9);
+        assert!(report.is_equal(), "{}", report);
+        assert_eq!(report.compared, 2);
+        assert!(report.max_absolute_difference > 0.0 && report.max_absolute_difference < 1e-9);
+
+        let report = left.compare(&right, 0.0);
+        assert_eq!((report.mismatched, report.differences()), (1, 1));
+        assert_eq!(
+            report.samples,
+            [PairDifference { from: 0, to: 1, left: Some(0.3), right: Some(0.1 + 0.2) }]
+        );
+    }
+
+    #[test]
+    fn missing_pairs_are_counted_on_their_side() {
+        let mut left = APSPResult::<u32>::new(3);
+        // The right result has a node more, its pairs are missing on the left
+        let mut right = APSPResult::<u32>::new(4);
+        left.add(0, 1, 5);
+        right.add(0, 1, 5);
+        left.add(1, 0, 7);
+        right.add(0, 2, 2);
+        right.add(3, 0, 1);
+        // Large distances are compared without an overflow
+        left.add(2, 0, u32::MAX_FINITE);
+        right.add(2, 0, u32::MAX_FINITE - 1);
+
+        let report = left.compare(&right, 0);
+        assert_eq!(report.compared, 2);
+        assert_eq!(report.mismatched, 1);
+        assert_eq!(report.missing_left, 2);
+        assert_eq!(report.missing_right, 1);
+        assert_eq!(report.max_absolute_difference, 1.0);
+        assert_eq!(
+            report.samples,
+            [
+                PairDifference { from: 0, to: 2, left: None, right: Some(2) },
+                PairDifference { from: 1, to: 0, left: Some(7), right: None },
+                PairDifference {
+                    from: 2,
+                    to: 0,
+                    left: Some(u32::MAX_FINITE),
+                    right: Some(u32::MAX_FINITE - 1),
+                },
+                PairDifference { from: 3, to: 0, left: None, right: Some(1) },
+            ]
+        );
+        assert!(left.compare(&left, 0).is_equal());
+
+        // Only the first differences are kept as samples
+        let full = APSPResult::from_matrix(Matrix::new(4, 4, 1u32), None);
+        let report = full.compare(&APSPResult::new(4), 0);
+        assert_eq!(report.missing_right, 16);
+        assert_eq!(report.samples.len(), MAX_SAMPLES);
+    }
+}
@@ -0,0 +1,514 @@
+// Binary file format for computed distance matrices, such that a solved APSP can be saved and
+// loaded again (or memory-mapped) instead of computed again
+//
+// Layout, all numbers little-endian:
+//   0..8    magic "APSPDIST"
+//   8..10   version (u16), currently 1
+//   10      weight type, see WeightType
+//   11      infinity encoding, see InfinityEncoding
+//   12      flags, bit 0 is set if the graph is directed
+//   13..16  reserved, zero
+//   16..24  node count n (u64)
+//   24..32  reserved, zero
+//   32..    the n x n distances row after row
+// 30-01-2024
+
+// Import crates
+use memmap2::Mmap;
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    fs::File,
+    io::{self, BufWriter, Write},
+    marker::PhantomData,
+};
+
+// Import crates from our files
+use crate::base::DistanceMatrix;
+use crate::matrix::Matrix;
+use crate::weight::Weight;
+
+const MAGIC: &[u8; 8] = b"APSPDIST";
+const VERSION: u16 = 1;
+const HEADER_SIZE: usize = 32;
+const FLAG_DIRECTED: u8 = 1;
+
+/// The weight type of the distances in a file
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WeightType {
+    U8 = 1,
+    U16 = 2,
+    U32 = 3,
+    U64 = 4,
+    U128 = 5,
+    I8 = 6,
+    I16 = 7,
+    I32 = 8,
+    I64 = 9,
+    I128 = 10,
+    F32 = 11,
+    F64 = 12,
+}
+
+impl WeightType {
+    const ALL: [WeightType; 12] = [
+        WeightType::U8,
+        WeightType::U16,
+        WeightType::U32,
+        WeightType::U64,
+        WeightType::U128,
+        WeightType::I8,
+        WeightType::I16,
+        WeightType::I32,
+        WeightType::I64,
+        WeightType::I128,
+        WeightType::F32,
+        WeightType::F64,
+    ];
+
+    fn from_code(code: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|weight_type| *weight_type as u8 == code)
+    }
+
+    /// The number of bytes of one distance
+    pub fn size(self) -> usize {
+        match self {
+            WeightType::U8 | WeightType::I8 => 1,
+            WeightType::U16 | WeightType::I16 => 2,
+            WeightType::U32 | WeightType::I32 | WeightType::F32 => 4,
+            WeightType::U64 | WeightType::I64 | WeightType::F64 => 8,
+            WeightType::U128 | WeightType::I128 => 16,
+        }
+    }
+}
+
+impl Display for WeightType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            WeightType::U8 => "u8",
+            WeightType::U16 => "u16",
+            WeightType::U32 => "u32",
+            WeightType::U64 => "u64",
+            WeightType::U128 => "u128",
+            WeightType::I8 => "i8",
+            WeightType::I16 => "i16",
+            WeightType::I32 => "i32",
+            WeightType::I64 => "i64",
+            WeightType::I128 => "i128",
+            WeightType::F32 => "f32",
+            WeightType::F64 => "f64",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// How a pair without a path is stored
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InfinityEncoding {
+    /// The largest value of the integer type, see [`Weight::INFINITY`]
+    Maximum = 0,
+    /// The IEEE positive infinity of the float type
+    Ieee = 1,
+}
+
+/// Weight types which can be stored in a distance file. usize and isize are left out, since
+/// their size depends on the platform
+pub trait StoredWeight: Weight {
+    const WEIGHT_TYPE: WeightType;
+    const INFINITY_ENCODING: InfinityEncoding;
+
+    /// Append the little-endian bytes of the value
+    fn write_le(self, out: &mut Vec<u8>);
+
+    /// Read a value from its little-endian bytes, bytes has exactly the size of the type
+    fn read_le(bytes: &[u8]) -> Self;
+}
+
+macro_rules! impl_stored_weight {
+    ($($t: ty => $weight_type: ident, $encoding: ident);* $(;)?) => {
+        $(
+            impl StoredWeight for $t {
+                const WEIGHT_TYPE: WeightType = WeightType::$weight_type;
+                const INFINITY_ENCODING: InfinityEncoding = InfinityEncoding::$encoding;
+
+                #[inline]
+                fn write_le(self, out: &mut Vec<u8>) {
+                    out.extend_from_slice(&self.to_le_bytes());
+                }
+
+                #[inline]
+                fn read_le(bytes: &[u8]) -> Self {
+                    <$t>::from_le_bytes(bytes.try_into().expect("wrong number of bytes"))
+                }
+            }
+        )*
+    };
+}
+
+impl_stored_weight!(
+    u8 => U8, Maximum;
+    u16 => U16, Maximum;
+    u32 => U32, Maximum;
+    u64 => U64, Maximum;
+    u128 => U128, Maximum;
+    i8 => I8, Maximum;
+    i16 => I16, Maximum;
+    i32 => I32, Maximum;
+    i64 => I64, Maximum;
+    i128 => I128, Maximum;
+    f32 => F32, Ieee;
+    f64 => F64, Ieee;
+);
+
+/// The header of a distance file
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DistanceFileHeader {
+    pub version: u16,
+    pub weight_type: WeightType,
+    pub infinity: InfinityEncoding,
+    pub directed: bool,
+    pub node_count: usize,
+}
+
+impl DistanceFileHeader {
+    fn to_bytes(self) -> [u8; HEADER_SIZE] {
+        let mut bytes = [0; HEADER_SIZE];
+        bytes[0..8].copy_from_slice(MAGIC);
+        bytes[8..10].copy_from_slice(&self.version.to_le_bytes());
+        bytes[10] = self.weight_type as u8;
+        bytes[11] = self.infinity as u8;
+        bytes[12] = if self.directed { FLAG_DIRECTED } else { 0 };
+        bytes[16..24].copy_from_slice(&(self.node_count as u64).to_le_bytes());
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8], path: &str) -> Result<Self, DistanceFileError> {
+        if bytes.len() < HEADER_SIZE || &bytes[0..8] != MAGIC {
+            return Err(DistanceFileError::NotADistanceFile {
+                path: path.to_string(),
+            });
+        }
+
+        let version = u16::from_le_bytes([bytes[8], bytes[9]]);
+        if version != VERSION {
+            return Err(DistanceFileError::UnsupportedVersion {
+                path: path.to_string(),
+                version,
+            });
+        }
+
+        let invalid = |reason: &str| DistanceFileError::InvalidHeader {
+            path: path.to_string(),
+            reason: reason.to_string(),
+        };
+        let weight_type =
+            WeightType::from_code(bytes[10]).ok_or_else(|| invalid("unknown weight type"))?;
+        let infinity = match bytes[11] {
+            0 => InfinityEncoding::Maximum,
+            1 => InfinityEncoding::Ieee,
+            _ => return Err(invalid("unknown infinity encoding")),
+        };
+        let node_count = u64::from_le_bytes(bytes[16..24].try_into().expect("slice of 8 bytes"));
+        let node_count = usize::try_from(node_count).map_err(|_| invalid("too many nodes"))?;
+
+        Ok(DistanceFileHeader {
+            version,
+            weight_type,
+            infinity,
+            directed: bytes[12] & FLAG_DIRECTED != 0,
+            node_count,
+        })
+    }
+
+    // The size of the whole file, None if it does not fit in memory anyway
+    fn file_size(&self) -> Option<usize> {
+        self.node_count
+            .checked_mul(self.node_count)?
+            .checked_mul(self.weight_type.size())?
+            .checked_add(HEADER_SIZE)
+    }
+}
+
+/// Errors that can occur while reading or writing a distance file
+#[derive(Debug)]
+pub enum DistanceFileError {
+    /// The file could not be opened, read or written
+    Io { path: String, source: io::Error },
+    /// The file does not start with the magic bytes of a distance file
+    NotADistanceFile { path: String },
+    /// The file was written by a newer version of the format
+    UnsupportedVersion { path: String, version: u16 },
+    /// The header contains a value that is not valid
+    InvalidHeader { path: String, reason: String },
+    /// The distances in the file have another type than the one asked for
+    WeightTypeMismatch { path: String, expected: WeightType, found: WeightType },
+    /// The file is not as long as the header says
+    SizeMismatch { path: String, expected: usize, found: usize },
+    /// The distance matrix to write does not have as many columns as rows
+    NotSquare { path: String, rows: usize, cols: usize },
+}
+
+impl Display for DistanceFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DistanceFileError::Io { path, source } => write!(f, "{}: {}", path, source),
+            DistanceFileError::NotADistanceFile { path } => {
+                write!(f, "{}: not a distance file", path)
+            }
+            DistanceFileError::UnsupportedVersion { path, version } => {
+                write!(f, "{}: unsupported version {}", path, version)
+            }
+            DistanceFileError::InvalidHeader { path, reason } => {
+                write!(f, "{}: invalid header, {}", path, reason)
+            }
+            DistanceFileError::WeightTypeMismatch { path, expected, found } => write!(
+                f,
+                "{}: the distances are of type {}, expected {}",
+                path, found, expected
+            ),
+            DistanceFileError::SizeMismatch { path, expected, found } => write!(
+                f,
+                "{}: expected {} bytes, found {}",
+                path, expected, found
+            ),
+            DistanceFileError::NotSquare { path, rows, cols } => write!(
+                f,
+                "{}: a distance matrix must be square, found {} rows and {} columns",
+                path, rows, cols
+            ),
+        }
+    }
+}
+
+impl Error for DistanceFileError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            DistanceFileError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+fn io_error(path: &str) -> impl FnOnce(io::Error) -> DistanceFileError + '_ {
+    move |source| DistanceFileError::Io {
+        path: path.to_string(),
+        source,
+    }
+}
+
+/// Write a square distance matrix to a distance file. directed is only stored in the header,
+/// such that the reader knows if the distance from i to j equals the one from j to i
+pub fn write_distance_file<W: StoredWeight>(
+    file_path: &str,
+    distance: &DistanceMatrix<W>,
+    directed: bool,
+) -> Result<(), DistanceFileError> {
+    if distance.rows() != distance.cols() {
+        return Err(DistanceFileError::NotSquare {
+            path: file_path.to_string(),
+            rows: distance.rows(),
+            cols: distance.cols(),
+        });
+    }
+
+    let header = DistanceFileHeader {
+        version: VERSION,
+        weight_type: W::WEIGHT_TYPE,
+        infinity: W::INFINITY_ENCODING,
+        directed,
+        node_count: distance.rows(),
+    };
+
+    let file = File::create(file_path).map_err(io_error(file_path))?;
+    let mut writer = BufWriter::new(file);
+    writer.write_all(&header.to_bytes()).map_err(io_error(file_path))?;
+
+    // Convert one row at a time, such that the buffer stays small for big matrices
+    let mut buffer = Vec::with_capacity(distance.cols() * W::WEIGHT_TYPE.size());
+    for row in distance.iter_rows().take(distance.rows()) {
+        buffer.clear();
+        for &value in row {
+            value.write_le(&mut buffer);
+        }
+        writer.write_all(&buffer).map_err(io_error(file_path))?;
+    }
+    writer.flush().map_err(io_error(file_path))
+}
+
+/// Read a whole distance file into memory
+pub fn read_distance_file<W: StoredWeight>(
+    file_path: &str,
+) -> Result<(DistanceFileHeader, DistanceMatrix<W>), DistanceFileError> {
+    let mapped = MappedDistances::<W>::open(file_path)?;
+    Ok((*mapped.header(), mapped.to_matrix()))
+}
+
+/// A distance file mapped into memory. Opening it is instant, the distances are only read from
+/// disk when they are used
+pub struct MappedDistances<W> {
+    header: DistanceFileHeader,
+    map: Mmap,
+    weight: PhantomData<W>,
+}
+
+impl<W: StoredWeight> MappedDistances<W> {
+    /// Map a distance file with distances of type W. The file must not be changed while it is
+    /// mapped, otherwise the distances that are read can change as well
+    pub fn open(file_path: &str) -> Result<Self, DistanceFileError> {
+        let file = File::open(file_path).map_err(io_error(file_path))?;
+        // Safety: the map is only read, see the note on changing the file above
+        let map = unsafe { Mmap::map(&file) }.map_err(io_error(file_path))?;
+
+        let header = DistanceFileHeader::from_bytes(&map, file_path)?;
+        if header.weight_type != W::WEIGHT_TYPE {
+            return Err(DistanceFileError::WeightTypeMismatch {
+                path: file_path.to_string(),
+                expected: W::WEIGHT_TYPE,
+                found: header.weight_type,
+            });
+        }
+        if header.infinity != W::INFINITY_ENCODING {
+            return Err(DistanceFileError::InvalidHeader {
+                path: file_path.to_string(),
+                reason: format!("unexpected infinity encoding for {}", header.weight_type),
+            });
+        }
+
+        let expected = header.file_size().ok_or_else(|| DistanceFileError::InvalidHeader {
+            path: file_path.to_string(),
+            reason: "too many nodes".to_string(),
+        })?;
+        if map.len() != expected {
+            return Err(DistanceFileError::SizeMismatch {
+                path: file_path.to_string(),
+                expected,
+                found: map.len(),
+            });
+        }
+
+        Ok(MappedDistances {
+            header,
+            map,
+            weight: PhantomData,
+        })
+    }
+
+    pub fn header(&self) -> &DistanceFileHeader {
+        &self.header
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.header.node_count
+    }
+
+    /// The distance from -> to, INFINITY if there is no path. None if a node is not in the graph
+    pub fn get(&self, from: usize, to: usize) -> Option<W> {
+        let n = self.node_count();
+        if from >= n || to >= n {
+            return None;
+        }
+        let size = W::WEIGHT_TYPE.size();
+        let start = HEADER_SIZE + (from * n + to) * size;
+        Some(W::read_le(&self.map[start..start + size]))
+    }
+
+    /// The distance from -> to, None if there is no path or if a node is not in the graph, the
+    /// same as [`crate::APSPResult::get`]
+    pub fn distance(&self, from: usize, to: usize) -> Option<W> {
+        self.get(from, to)?.finite()
+    }
+
+    /// The distances from one node to all nodes, INFINITY if there is no path. None if the node
+    /// is not in the graph
+    pub fn row(&self, from: usize) -> Option<impl Iterator<Item = W> + '_> {
+        let n = self.node_count();
+        if from >= n {
+            return None;
+        }
+        let size = W::WEIGHT_TYPE.size();
+        let start = HEADER_SIZE + from * n * size;
+        Some(self.map[start..start + n * size].chunks_exact(size).map(W::read_le))
+    }
+
+    /// Copy all distances into a matrix
+    pub fn to_matrix(&self) -> DistanceMatrix<W> {
+        let n = self.node_count();
+        let mut distance = Matrix::new(n, n, W::INFINITY);
+        let size = W::WEIGHT_TYPE.size();
+        let bytes = self.map[HEADER_SIZE..].chunks_exact(size);
+        for (value, bytes) in distance.as_mut_slice().iter_mut().zip(bytes) {
+            *value = W::read_le(bytes);
+        }
+        distance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempFile;
+
+    #[test]
+    fn write_then_read_integer_distances() {
+        let file = TempFile::new("u16.apsp");
+        let path = &file.path;
+        let mut distance = Matrix::new(3, 3, u16::INFINITY);
+        distance[(0, 0)] = 0;
+        distance[(0, 1)] = 7;
+        distance[(1, 2)] = 300;
+        write_distance_file(path, &distance, true).unwrap();
+
+        let (header, read) = read_distance_file::<u16>(path).unwrap();
+        assert_eq!(
+            header,
+            DistanceFileHeader {
+                version: VERSION,
+                weight_type: WeightType::U16,
+                infinity: InfinityEncoding::Maximum,
+                directed: true,
+                node_count: 3,
+            }
+        );
+        assert_eq!(read.as_slice(), distance.as_slice());
+        assert_eq!(read[(2, 0)], u16::MAX);
+    }
+
+    #[test]
+    fn write_then_read_float_distances() {
+        let file = TempFile::new("f64.apsp");
+        let path = &file.path;
+        let mut distance = Matrix::new(2, 2, f64::INFINITY);
+        distance[(0, 1)] = -1.5;
+        write_distance_file(path, &distance, false).unwrap();
+
+        let mapped = MappedDistances::<f64>::open(path).unwrap();
+        assert_eq!(mapped.header().infinity, InfinityEncoding::Ieee);
+        assert!(!mapped.header().directed);
+        assert_eq!(mapped.distance(0, 1), Some(-1.5));
+        assert_eq!(mapped.distance(1, 0), None);
+        assert_eq!(mapped.get(1, 0), Some(f64::INFINITY));
+        assert_eq!(mapped.row(0).unwrap().collect::<Vec<_>>(), [f64::INFINITY, -1.5]);
+
+        // A node which is not in the graph has no distances
+        assert_eq!(mapped.get(2, 0), None);
+        assert_eq!(mapped.distance(0, 2), None);
+        assert!(mapped.row(2).is_none());
+
+        // The file holds f64 distances, reading them as another type fails
+        assert!(matches!(
+            MappedDistances::<f32>::open(path),
+            Err(DistanceFileError::WeightTypeMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn non_square_matrix_is_rejected() {
+        let file = TempFile::new("square.apsp");
+        let distance = Matrix::new(2, 3, 0u32);
+        assert!(matches!(
+            write_distance_file(&file.path, &distance, true),
+            Err(DistanceFileError::NotSquare { rows: 2, cols: 3, .. })
+        ));
+        assert!(!std::path::Path::new(&file.path).exists());
+    }
+}
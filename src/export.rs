@@ -0,0 +1,707 @@
+// Writers for the graph formats and the distance matrices, such that graphs which are generated or
+// transformed in Rust can be used by other tools, and loaded again with [`crate::FromGraphFile`]
+// Every graph type only has to list its edges, the writers are shared
+// 30-01-2024
+
+// Import crates
+use num::Num;
+use petgraph::graph::Graph;
+use petgraph::matrix_graph::MatrixGraph;
+use petgraph::visit::{EdgeRef, IntoEdgeReferences, NodeIndexable};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+// Import crates from our files
+use crate::base::{APSPResult, DistanceMatrix};
+use crate::graph::{EdgeList, GraphAM, GraphCSR, NodeIdGraph};
+use crate::labels::Labels;
+use crate::loader::GraphFormat;
+use crate::weight::Weight;
+
+/// Errors that can occur while writing a graph or a distance matrix
+#[derive(Debug)]
+pub enum ExportError {
+    /// The file could not be created or written
+    Io { path: String, source: io::Error },
+    /// The dense format has a weight for every pair, so every pair of different nodes needs an
+    /// edge. A missing edge from a node to itself is written as 0
+    MissingEdge { path: String, from: usize, to: usize },
+    /// An undirected file needs the reverse of every edge, with the same weight
+    NotSymmetric { path: String, from: usize, to: usize },
+    /// A label of a sparse file can't contain a double quote or a line break
+    InvalidLabel { path: String, label: String },
+}
+
+impl Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportError::Io { path, source } => write!(f, "{}: {}", path, source),
+            ExportError::MissingEdge { path, from, to } => write!(
+                f,
+                "{}: no edge from {} to {}, the dense format needs all edges",
+                path, from, to
+            ),
+            ExportError::NotSymmetric { path, from, to } => write!(
+                f,
+                "{}: the edge from {} to {} has no reverse with the same weight",
+                path, from, to
+            ),
+            ExportError::InvalidLabel { path, label } => {
+                write!(f, "{}: can't write the label \"{}\"", path, label)
+            }
+        }
+    }
+}
+
+impl Error for ExportError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ExportError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+fn io_error(path: &str) -> impl FnOnce(io::Error) -> ExportError + '_ {
+    move |source| ExportError::Io {
+        path: path.to_string(),
+        source,
+    }
+}
+
+/// Options for writing a graph
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ExportOptions<'a> {
+    /// Write every edge once and mark the file as undirected (not possible for DIMACS, which is
+    /// always directed). The graph needs the reverse of every edge with the same weight
+    pub undirected: bool,
+    /// Write the nodes of the sparse, CSV and GraphML files by label. The loaders give the
+    /// labels new indices, in the order in which they appear in the file
+    pub labels: Option<&'a Labels>,
+}
+
+impl<'a> ExportOptions<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn undirected(mut self, undirected: bool) -> Self {
+        self.undirected = undirected;
+        self
+    }
+
+    pub fn labels(mut self, labels: &'a Labels) -> Self {
+        self.labels = Some(labels);
+        self
+    }
+}
+
+pub trait ToGraphFile<W>
+where
+    W: Num + Copy + Display,
+{
+    /// The nodes and edges of the graph, the nodes are numbered 0..n
+    fn to_edge_list(&self) -> EdgeList<W>;
+    /// Write the graph in the sparse format, every edge as a directed edge
+    fn to_sparse_file(&self, file_path: &str) -> Result<(), ExportError> {
+        self.to_file_with(file_path, GraphFormat::Sparse, &ExportOptions::new())
+    }
+    /// Write the graph in the dense format, which needs an edge between every pair of nodes
+    fn to_dense_file(&self, file_path: &str) -> Result<(), ExportError> {
+        self.to_file_with(file_path, GraphFormat::Dense, &ExportOptions::new())
+    }
+    /// Write the graph in any of the formats the graphs can be loaded from
+    fn to_file_with(
+        &self,
+        file_path: &str,
+        format: GraphFormat,
+        options: &ExportOptions,
+    ) -> Result<(), ExportError> {
+        write_graph(file_path, format, &self.to_edge_list(), options)
+    }
+}
+
+/// Write the edges to a file in the format, see [`ToGraphFile::to_file_with`]
+pub fn write_graph<W>(
+    file_path: &str,
+    format: GraphFormat,
+    graph: &EdgeList<W>,
+    options: &ExportOptions,
+) -> Result<(), ExportError>
+where
+    W: Num + Copy + Display,
+{
+    // Check that the graph can be written before the file is created
+    let undirected = options.undirected && format != GraphFormat::Dimacs;
+    let edges = written_edges(file_path, graph, undirected)?;
+    let matrix = match format {
+        GraphFormat::Dense => dense_matrix(file_path, graph)?,
+        _ => Vec::new(),
+    };
+    if let (GraphFormat::Sparse, Some(labels)) = (format, options.labels) {
+        check_labels(file_path, labels)?;
+    }
+
+    let file = File::create(file_path).map_err(io_error(file_path))?;
+    let mut writer = BufWriter::new(file);
+    match format {
+        GraphFormat::Sparse => {
+            write_sparse(&mut writer, graph, &edges, undirected, options.labels)
+                .map_err(io_error(file_path))
+        }
+        GraphFormat::Dense => {
+            write_dense(&mut writer, graph.node_count, &matrix).map_err(io_error(file_path))
+        }
+        GraphFormat::Dimacs => {
+            write_dimacs(&mut writer, graph, &edges).map_err(io_error(file_path))
+        }
+        GraphFormat::MatrixMarket => {
+            write_matrix_market(&mut writer, graph, &edges, undirected)
+                .map_err(io_error(file_path))
+        }
+        GraphFormat::GraphML => {
+            write_graphml(&mut writer, graph, &edges, undirected, options.labels)
+                .map_err(io_error(file_path))
+        }
+        GraphFormat::Csv => {
+            write_csv(&mut writer, &edges, options.labels).map_err(io_error(file_path))
+        }
+    }?;
+    writer.flush().map_err(io_error(file_path))
+}
+
+// The edges as they are written, for an undirected file only the edges with from <= to. These are
+// the lower triangle of a symmetric Matrix Market file once the rows and columns are swapped
+fn written_edges<W>(
+    file_path: &str,
+    graph: &EdgeList<W>,
+    undirected: bool,
+) -> Result<Vec<(usize, usize, W)>, ExportError>
+where
+    W: Num + Copy,
+{
+    let edges = graph.edges.iter().map(|edge| (edge.from, edge.to, edge.weight));
+    if !undirected {
+        return Ok(edges.collect());
+    }
+
+    let weights: HashMap<(usize, usize), W> = edges.clone().map(|(a, b, w)| ((a, b), w)).collect();
+    for (from, to, weight) in edges.clone() {
+        if weights.get(&(to, from)) != Some(&weight) {
+            return Err(ExportError::NotSymmetric {
+                path: file_path.to_string(),
+                from,
+                to,
+            });
+        }
+    }
+    Ok(edges.filter(|(from, to, _)| from <= to).collect())
+}
+
+// True for the integer weight types, in which 1 / 2 rounds to 0
+fn is_integer<W: Num>() -> bool {
+    W::one() / (W::one() + W::one()) == W::zero()
+}
+
+// The label of a node, or its id (from 1 in the sparse format) if there are no labels
+fn node_name(node: usize, offset: usize, labels: Option<&Labels>) -> String {
+    match labels.and_then(|labels| labels.label(node)) {
+        Some(label) => label.to_string(),
+        None => (node + offset).to_string(),
+    }
+}
+
+// The labels of a sparse file are separated by whitespace, so a label with whitespace is quoted.
+// There is no escape for a quote, and a label can't span lines
+fn check_labels(file_path: &str, labels: &Labels) -> Result<(), ExportError> {
+    match labels.iter().find(|label| label.contains(['"', '\n', '\r'])) {
+        Some(label) => Err(ExportError::InvalidLabel {
+            path: file_path.to_string(),
+            label: label.to_string(),
+        }),
+        None => Ok(()),
+    }
+}
+
+fn write_sparse<W>(
+    writer: &mut impl Write,
+    graph: &EdgeList<W>,
+    edges: &[(usize, usize, W)],
+    undirected: bool,
+    labels: Option<&Labels>,
+) -> io::Result<()>
+where
+    W: Num + Copy + Display,
+{
+    let name = |node: usize| {
+        let name = node_name(node, 1, labels);
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            format!("\"{}\"", name)
+        } else {
+            name
+        }
+    };
+
+    let direction = if undirected { "undirected" } else { "directed" };
+    let labelled = if labels.is_some() { " labelled" } else { "" };
+    writeln!(writer, "{} {} {}{}", graph.node_count, edges.len(), direction, labelled)?;
+    for &(from, to, weight) in edges {
+        writeln!(writer, "{} {} {}", name(from), name(to), weight)?;
+    }
+    Ok(())
+}
+
+// The full adjacency matrix of the dense format, every pair of different nodes needs an edge
+fn dense_matrix<W>(file_path: &str, graph: &EdgeList<W>) -> Result<Vec<W>, ExportError>
+where
+    W: Num + Copy,
+{
+    let n = graph.node_count;
+    let mut matrix: Vec<Option<W>> = vec![None; n * n];
+    for edge in &graph.edges {
+        matrix[edge.from * n + edge.to] = Some(edge.weight);
+    }
+
+    let mut weights = Vec::with_capacity(n * n);
+    for (index, weight) in matrix.into_iter().enumerate() {
+        let (from, to) = (index / n, index % n);
+        match weight {
+            Some(weight) => weights.push(weight),
+            None if from == to => weights.push(W::zero()),
+            None => {
+                return Err(ExportError::MissingEdge {
+                    path: file_path.to_string(),
+                    from,
+                    to,
+                })
+            }
+        }
+    }
+    Ok(weights)
+}
+
+fn write_dense<W>(writer: &mut impl Write, node_count: usize, matrix: &[W]) -> io::Result<()>
+where
+    W: Display,
+{
+    writeln!(writer, "{}", node_count)?;
+    for row in matrix.chunks(node_count.max(1)) {
+        let row: Vec<String> = row.iter().map(|weight| weight.to_string()).collect();
+        writeln!(writer, "{}", row.join(" "))?;
+    }
+    Ok(())
+}
+
+fn write_dimacs<W>(
+    writer: &mut impl Write,
+    graph: &EdgeList<W>,
+    edges: &[(usize, usize, W)],
+) -> io::Result<()>
+where
+    W: Num + Copy + Display,
+{
+    writeln!(writer, "p sp {} {}", graph.node_count, edges.len())?;
+    for &(from, to, weight) in edges {
+        writeln!(writer, "a {} {} {}", from + 1, to + 1, weight)?;
+    }
+    Ok(())
+}
+
+fn write_matrix_market<W>(
+    writer: &mut impl Write,
+    graph: &EdgeList<W>,
+    edges: &[(usize, usize, W)],
+    undirected: bool,
+) -> io::Result<()>
+where
+    W: Num + Copy + Display,
+{
+    let field = if is_integer::<W>() { "integer" } else { "real" };
+    let symmetry = if undirected { "symmetric" } else { "general" };
+    writeln!(writer, "%%MatrixMarket matrix coordinate {} {}", field, symmetry)?;
+    let n = graph.node_count;
+    writeln!(writer, "{} {} {}", n, n, edges.len())?;
+    // Only the lower triangle of a symmetric matrix is stored, the row is the larger node
+    for &(from, to, weight) in edges {
+        let (row, column) = if undirected { (to, from) } else { (from, to) };
+        writeln!(writer, "{} {} {}", row + 1, column + 1, weight)?;
+    }
+    Ok(())
+}
+
+// Escape the characters with a meaning in XML
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn write_graphml<W>(
+    writer: &mut impl Write,
+    graph: &EdgeList<W>,
+    edges: &[(usize, usize, W)],
+    undirected: bool,
+    labels: Option<&Labels>,
+) -> io::Result<()>
+where
+    W: Num + Copy + Display,
+{
+    // Without labels the nodes are called n0, n1, ... as in most GraphML files
+    let name = |node: usize| match labels.and_then(|labels| labels.label(node)) {
+        Some(label) => xml_escape(label),
+        None => format!("n{}", node),
+    };
+
+    let attribute_type = if is_integer::<W>() { "long" } else { "double" };
+    let edge_default = if undirected { "undirected" } else { "directed" };
+    writeln!(writer, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(writer, "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">")?;
+    writeln!(
+        writer,
+        "  <key id=\"weight\" for=\"edge\" attr.name=\"weight\" attr.type=\"{}\"/>",
+        attribute_type
+    )?;
+    writeln!(writer, "  <graph id=\"G\" edgedefault=\"{}\">", edge_default)?;
+    for node in 0..graph.node_count {
+        writeln!(writer, "    <node id=\"{}\"/>", name(node))?;
+    }
+    for &(from, to, weight) in edges {
+        writeln!(
+            writer,
+            "    <edge source=\"{}\" target=\"{}\"><data key=\"weight\">{}</data></edge>",
+            name(from),
+            name(to),
+            weight
+        )?;
+    }
+    writeln!(writer, "  </graph>")?;
+    writeln!(writer, "</graphml>")
+}
+
+/// Quote a CSV field if it contains a separator, a quote or a line break
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn write_csv<W>(
+    writer: &mut impl Write,
+    edges: &[(usize, usize, W)],
+    labels: Option<&Labels>,
+) -> io::Result<()>
+where
+    W: Num + Copy + Display,
+{
+    writeln!(writer, "from,to,weight")?;
+    for &(from, to, weight) in edges {
+        let from = csv_field(&node_name(from, 0, labels));
+        let to = csv_field(&node_name(to, 0, labels));
+        writeln!(writer, "{},{},{}", from, to, weight)?;
+    }
+    Ok(())
+}
+
+/// Write a distance matrix as CSV, one row per node and `inf` for the pairs without a path. With
+/// labels the first row and column hold the labels of the nodes
+pub fn write_distances_csv<W>(
+    file_path: &str,
+    distances: &DistanceMatrix<W>,
+    labels: Option<&Labels>,
+) -> Result<(), ExportError>
+where
+    W: Weight + Display,
+{
+    let file = File::create(file_path).map_err(io_error(file_path))?;
+    let mut writer = BufWriter::new(file);
+    let n = distances.rows();
+    let distance = |from: usize, to: usize| match distances[(from, to)].finite() {
+        Some(distance) => distance.to_string(),
+        None => "inf".to_string(),
+    };
+
+    if let Some(labels) = labels {
+        let header: Vec<String> = (0..n)
+            .map(|node| csv_field(&node_name(node, 0, Some(labels))))
+            .collect();
+        writeln!(writer, ",{}", header.join(",")).map_err(io_error(file_path))?;
+    }
+    for from in 0..n {
+        let mut row: Vec<String> = (0..n).map(|to| distance(from, to)).collect();
+        if let Some(labels) = labels {
+            row.insert(0, csv_field(&node_name(from, 0, Some(labels))));
+        }
+        writeln!(writer, "{}", row.join(",")).map_err(io_error(file_path))?;
+    }
+    writer.flush().map_err(io_error(file_path))
+}
+
+/// Write a distance matrix as a Matrix Market coordinate matrix, with an entry for every pair with
+/// a path (including the zeros on the diagonal)
+pub fn write_distances_matrix_market<W>(
+    file_path: &str,
+    distances: &DistanceMatrix<W>,
+) -> Result<(), ExportError>
+where
+    W: Weight + Display,
+{
+    let file = File::create(file_path).map_err(io_error(file_path))?;
+    let mut writer = BufWriter::new(file);
+    let n = distances.rows();
+    let entries: Vec<(usize, usize, W)> = (0..n)
+        .flat_map(|from| (0..n).map(move |to| (from, to)))
+        .filter_map(|(from, to)| Some((from, to, distances[(from, to)].finite()?)))
+        .collect();
+
+    let field = if is_integer::<W>() { "integer" } else { "real" };
+    let write = |writer: &mut BufWriter<File>| -> io::Result<()> {
+        writeln!(writer, "%%MatrixMarket matrix coordinate {} general", field)?;
+        writeln!(writer, "{} {} {}", n, n, entries.len())?;
+        for &(from, to, distance) in &entries {
+            writeln!(writer, "{} {} {}", from + 1, to + 1, distance)?;
+        }
+        Ok(())
+    };
+    write(&mut writer).map_err(io_error(file_path))?;
+    writer.flush().map_err(io_error(file_path))
+}
+
+impl<W: Weight + Display> APSPResult<W> {
+    /// Write the distances as CSV, see [`write_distances_csv`]
+    pub fn to_csv_file(&self, file_path: &str, labels: Option<&Labels>) -> Result<(), ExportError> {
+        write_distances_csv(file_path, &self.shortest_paths, labels)
+    }
+
+    /// Write the distances as a Matrix Market file, see [`write_distances_matrix_market`]
+    pub fn to_matrix_market_file(&self, file_path: &str) -> Result<(), ExportError> {
+        write_distances_matrix_market(file_path, &self.shortest_paths)
+    }
+}
+
+impl<W: Num + Copy + Display> ToGraphFile<W> for EdgeList<W> {
+    fn to_edge_list(&self) -> EdgeList<W> {
+        let mut graph = EdgeList::with_capacity(self.node_count, self.edges.len());
+        graph.edges.extend_from_slice(&self.edges);
+        graph
+    }
+}
+
+impl<W: Num + Copy + Display> ToGraphFile<W> for GraphAM<W> {
+    fn to_edge_list(&self) -> EdgeList<W> {
+        let mut graph = EdgeList::with_capacity(self.node_count, 0);
+        for (from, row) in self.adjacency_matrix.iter().enumerate() {
+            for (to, weight) in row.iter().enumerate() {
+                if let Some(weight) = weight {
+                    graph.add_edge(from, to, *weight);
+                }
+            }
+        }
+        graph
+    }
+}
+
+impl<W: Num + Copy + Display> ToGraphFile<W> for GraphCSR<W> {
+    fn to_edge_list(&self) -> EdgeList<W> {
+        let mut graph = EdgeList::with_capacity(self.node_count, self.edge_count());
+        for from in 0..self.node_count {
+            for (to, weight) in self.neighbours(from) {
+                graph.add_edge(from, to, weight);
+            }
+        }
+        graph
+    }
+}
+
+// The petgraph graphs are written by node index, which is the node id for the loaded graphs
+impl<W: Num + Copy + Display> ToGraphFile<W> for Graph<usize, W> {
+    fn to_edge_list(&self) -> EdgeList<W> {
+        let mut graph = EdgeList::with_capacity(self.node_count(), self.edge_count());
+        for edge in self.edge_references() {
+            graph.add_edge(edge.source().index(), edge.target().index(), *edge.weight());
+        }
+        graph
+    }
+}
+
+impl<W: Num + Copy + Display> ToGraphFile<W> for MatrixGraph<usize, W> {
+    fn to_edge_list(&self) -> EdgeList<W> {
+        let mut graph = EdgeList::with_capacity(self.node_count(), self.edge_count());
+        for (from, to, weight) in self.edge_references() {
+            graph.add_edge(self.to_index(from), self.to_index(to), *weight);
+        }
+        graph
+    }
+}
+
+impl<W: Num + Copy + Display, G: ToGraphFile<W>, I> ToGraphFile<W> for NodeIdGraph<G, I> {
+    fn to_edge_list(&self) -> EdgeList<W> {
+        self.graph.to_edge_list()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::{FromGraphFile, LoadOptions};
+    use crate::testing::TempFile;
+
+    fn edge_list(node_count: usize, edges: &[(usize, usize, u32)]) -> EdgeList<u32> {
+        let mut graph = EdgeList::with_capacity(node_count, edges.len());
+        for &(from, to, weight) in edges {
+            graph.add_edge(from, to, weight);
+        }
+        graph
+    }
+
+    // The edges by the names of their nodes, such that graphs whose loader numbered the labels in
+    // another order can be compared
+    fn named_edges(graph: &EdgeList<u32>, labels: Option<&Labels>) -> Vec<(String, String, u32)> {
+        let mut edges: Vec<_> = graph
+            .edges
+            .iter()
+            .map(|edge| {
+                let name = |node| node_name(node, 0, labels);
+                (name(edge.from), name(edge.to), edge.weight)
+            })
+            .collect();
+        edges.sort();
+        edges
+    }
+
+    // Write the graph and load it again with the options the format needs
+    fn round_trip(
+        name: &str,
+        graph: &EdgeList<u32>,
+        format: GraphFormat,
+        options: &ExportOptions,
+        load_options: &LoadOptions,
+    ) -> (EdgeList<u32>, Option<Labels>) {
+        let file = TempFile::new(name);
+        write_graph(&file.path, format, graph, options).unwrap();
+        let file = EdgeList::<u32>::from_graph_file(&file.path, format, load_options).unwrap();
+        assert_eq!(file.graph.node_count, graph.node_count);
+        (file.graph, file.labels)
+    }
+
+    #[test]
+    fn directed_graph_loads_back() {
+        let graph = edge_list(4, &[(0, 1, 5), (1, 2, 7), (2, 0, 1), (3, 1, 9)]);
+        let directed = LoadOptions::new().directed(true);
+        let formats = [
+            ("directed.gph", GraphFormat::Sparse),
+            ("directed.gr", GraphFormat::Dimacs),
+            ("directed.mtx", GraphFormat::MatrixMarket),
+            ("directed.graphml", GraphFormat::GraphML),
+            ("directed.csv", GraphFormat::Csv),
+        ];
+        for (name, format) in formats {
+            let (read, _) = round_trip(name, &graph, format, &ExportOptions::new(), &directed);
+            assert_eq!(named_edges(&read, None), named_edges(&graph, None), "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn undirected_graph_loads_back() {
+        let graph = edge_list(3, &[(0, 1, 5), (1, 0, 5), (1, 2, 7), (2, 1, 7)]);
+        let options = ExportOptions::new().undirected(true);
+        let formats = [
+            ("undirected.gph", GraphFormat::Sparse),
+            ("undirected.mtx", GraphFormat::MatrixMarket),
+            ("undirected.graphml", GraphFormat::GraphML),
+            ("undirected.csv", GraphFormat::Csv),
+        ];
+        for (name, format) in formats {
+            let (read, _) = round_trip(name, &graph, format, &options, &LoadOptions::new());
+            assert_eq!(named_edges(&read, None), named_edges(&graph, None), "{:?}", format);
+        }
+
+        // DIMACS is always directed, so every edge is written
+        let directed = LoadOptions::new().directed(true);
+        let (read, _) =
+            round_trip("undirected.gr", &graph, GraphFormat::Dimacs, &options, &directed);
+        assert_eq!(named_edges(&read, None), named_edges(&graph, None));
+    }
+
+    #[test]
+    fn labelled_graph_loads_back() {
+        let mut labels = Labels::new();
+        for label in ["Central Station", "Airport", "a&b, <c>"] {
+            labels.intern(label);
+        }
+        let graph = edge_list(3, &[(2, 1, 4), (1, 0, 6), (0, 2, 8)]);
+        let options = ExportOptions::new().labels(&labels);
+        let load_options = LoadOptions::new().directed(true).labelled(true);
+        let formats = [
+            ("labelled.graphml", GraphFormat::GraphML),
+            ("labelled.csv", GraphFormat::Csv),
+        ];
+        for (name, format) in formats {
+            let (read, read_labels) = round_trip(name, &graph, format, &options, &load_options);
+            let expected = named_edges(&graph, Some(&labels));
+            assert_eq!(named_edges(&read, read_labels.as_ref()), expected, "{:?}", format);
+        }
+
+        // A label of the sparse format can't contain a quote, the others can be written there
+        let mut sparse_labels = Labels::new();
+        for label in ["Central Station", "Airport", "10.0.0.1"] {
+            sparse_labels.intern(label);
+        }
+        let options = ExportOptions::new().labels(&sparse_labels);
+        let load_options = LoadOptions::new().labelled(true);
+        let (read, read_labels) =
+            round_trip("labelled.gph", &graph, GraphFormat::Sparse, &options, &load_options);
+        let expected = named_edges(&graph, Some(&sparse_labels));
+        assert_eq!(named_edges(&read, read_labels.as_ref()), expected);
+    }
+
+    #[test]
+    fn complete_graph_loads_back_from_the_dense_format() {
+        let mut edges = Vec::new();
+        for from in 0..3 {
+            for to in 0..3 {
+                edges.push((from, to, if from == to { 0 } else { (3 * from + to) as u32 }));
+            }
+        }
+        let graph = edge_list(3, &edges);
+        let (read, _) = round_trip(
+            "complete.gph",
+            &graph,
+            GraphFormat::Dense,
+            &ExportOptions::new(),
+            &LoadOptions::new(),
+        );
+        let read: Vec<_> = named_edges(&read, None)
+            .into_iter()
+            .filter(|(from, to, _)| from != to)
+            .collect();
+        let expected: Vec<_> = named_edges(&graph, None)
+            .into_iter()
+            .filter(|(from, to, _)| from != to)
+            .collect();
+        assert_eq!(read, expected);
+    }
+
+    #[test]
+    fn graph_which_can_not_be_written_fails() {
+        let file = TempFile::new("not_written.gph");
+        let path = &file.path;
+        let graph = edge_list(3, &[(0, 1, 5), (1, 0, 6)]);
+        let undirected = ExportOptions::new().undirected(true);
+        assert!(matches!(
+            write_graph(path, GraphFormat::Sparse, &graph, &undirected),
+            Err(ExportError::NotSymmetric { from: 0, to: 1, .. })
+        ));
+        assert!(matches!(
+            write_graph(path, GraphFormat::Dense, &graph, &ExportOptions::new()),
+            Err(ExportError::MissingEdge { from: 0, to: 2, .. })
+        ));
+        // The errors are found before the file is created
+        assert!(!std::path::Path::new(path).exists());
+    }
+}
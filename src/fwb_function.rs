@@ -3,142 +3,314 @@
 // 30-01-2024
 
 // Import crates
-use num::Num;
 use std::clone::Clone;
-use std::cmp::PartialOrd;
-use std::fmt::Debug;
+use std::sync::RwLock;
 
+use crate::base::{has_negative_diagonal, PredecessorMatrix};
+use crate::matrix::Matrix;
+use crate::weight::Weight;
+
+// The kernels work on blocks (tiles) of the distance matrix. The output block is updated in
+// place. Row k of the right matrix is copied before it is used, because in some kernels the
+// right matrix is the output block itself. Row k does not change in round k (the diagonal is
+// zero), so this gives the same result and lets the inner loop run over two plain slices
 macro_rules! floyd_warshall_base {
-    ($left: expr, $right: expr, $out: expr, $b: expr) => {
-        // let b = $b;
-        let k_range = $b.min($left[0].len()).min($right.len());
-        let i_range = $b.min($left.len()).min($out.len());
-        let j_range = $b.min($right[0].len()).min($out[0].len());
+    ($left: expr, $right: expr, $out: expr) => {
+        let k_range = $left.cols().min($right.rows());
+        let i_range = $left.rows().min($out.rows());
+        let j_range = $right.cols().min($out.cols());
+
+        let mut right_row = Vec::with_capacity(j_range);
+        for k in 0..k_range {
+            right_row.clear();
+            right_row.extend_from_slice(&$right.row(k)[..j_range]);
+
+            for i in 0..i_range {
+                let aik = $left[(i, k)];
+                if aik.is_infinite() {
+                    continue;
+                }
+                for (cij, &bkj) in $out.row_mut(i)[..j_range].iter_mut().zip(&right_row) {
+                    let sum = aik.add_weight(bkj);
+                    if sum < *cij {
+                        *cij = sum;
+                    }
+                }
+            }
+        }
+    };
+    // Same as above, but also update the path steps of the output block. The predecessor of j
+    // on the path via k is the predecessor of j on the path from k, which is in the right matrix.
+    // Equal distances are broken on the number of hops, otherwise zero weight cycles can end up
+    // as cycles in the predecessors because the blocks are not visited in the order of plain FW
+    ($left: expr, $right: expr, $out: expr, $left_path: expr, $right_path: expr, $out_path: expr) => {
+        let k_range = $left.cols().min($right.rows());
+        let i_range = $left.rows().min($out.rows());
+        let j_range = $right.cols().min($out.cols());
 
+        let mut right_row = Vec::with_capacity(j_range);
+        let mut right_path_row = Vec::with_capacity(j_range);
         for k in 0..k_range {
+            right_row.clear();
+            right_row.extend_from_slice(&$right.row(k)[..j_range]);
+            right_path_row.clear();
+            right_path_row.extend_from_slice(&$right_path.row(k)[..j_range]);
+
             for i in 0..i_range {
+                let aik = $left[(i, k)];
+                if aik.is_infinite() {
+                    continue;
+                }
+                let hops_ik = hop_count($left_path[(i, k)]);
                 for j in 0..j_range {
-                    if let (Some(aik), Some(bkj)) = ($left[i][k], $right[k][j]) {
-                        let sum = aik + bkj;
-                        if let Some(cij) = $out[i][j] {
-                            if cij > sum {
-                                $out[i][j] = Some(sum);
-                            }
-                        } else {
-                            $out[i][j] = Some(sum);
-                        }
+                    let sum = aik.add_weight(right_row[j]);
+                    if sum.is_infinite() {
+                        continue;
+                    }
+                    let hops = hops_ik + hop_count(right_path_row[j]);
+                    let cij = $out[(i, j)];
+                    if sum < cij || (sum == cij && hops < hop_count($out_path[(i, j)])) {
+                        $out[(i, j)] = sum;
+                        $out_path[(i, j)] = right_path_row[j].map(|step: PathStep| PathStep {
+                            predecessor: step.predecessor,
+                            hops,
+                        });
                     }
                 }
             }
         }
-        return $out;
     };
 }
 
-// FW for one block where all matrices are the same (original FW algorithm)
-pub fn floyd_warshall_in_place1<W: Num + Copy + PartialOrd + Debug + Clone>(
-    matrix_c: &Vec<Vec<Option<W>>>,
-    b: usize,
-) -> Vec<Vec<Option<W>>> {
-    let mut result = matrix_c.to_owned();
+/// FW for one block where all matrices are the same (original FW algorithm)
+pub fn floyd_warshall_in_place1<W: Weight>(matrix_c: &mut Matrix<W>) {
+    floyd_warshall_base!(matrix_c, matrix_c, matrix_c);
+}
 
-    floyd_warshall_base!(result, result, result, b);
+/// FW in place where the output matrix is the same as the B matrix and where matrix A differs
+pub fn floyd_warshall_in_place2<W: Weight>(matrix_c: &mut Matrix<W>, matrix_a: &Matrix<W>) {
+    floyd_warshall_base!(matrix_a, matrix_c, matrix_c);
 }
 
-// FW in place where the output matrix is the same as the B matrix and where matrix A differs
-pub fn floyd_warshall_in_place2<W: Num + Copy + PartialOrd + Debug + Clone>(
-    matrix_c: &Vec<Vec<Option<W>>>,
-    matrix_a: &[Vec<Option<W>>],
-    b: usize,
-) -> Vec<Vec<Option<W>>> {
-    let mut result = matrix_c.to_owned();
-    floyd_warshall_base!(matrix_a, result, result, b);
+/// FW in place where the output matrix is the same as the A matrix and where matrix B differs
+pub fn floyd_warshall_in_place3<W: Weight>(matrix_c: &mut Matrix<W>, matrix_b: &Matrix<W>) {
+    floyd_warshall_base!(matrix_c, matrix_b, matrix_c);
 }
 
-// FW in place where the output matrix is the same as the A matrix and where matrix B differs
-pub fn floyd_warshall_in_place3<W: Num + Copy + PartialOrd + Debug + Clone>(
-    matrix_c: &Vec<Vec<Option<W>>>,
-    matrix_b: &Vec<Vec<Option<W>>>,
-    b: usize,
-) -> Vec<Vec<Option<W>>> {
-    let mut result = matrix_c.to_owned();
+/// FW in place where the output matrix, and matrix A and B are different.
+pub fn floyd_warshall_in_place4<W: Weight>(
+    matrix_c: &mut Matrix<W>,
+    matrix_a: &Matrix<W>,
+    matrix_b: &Matrix<W>,
+) {
+    floyd_warshall_base!(matrix_a, matrix_b, matrix_c);
+}
+
+/// One entry of the path matrix used by the blocked algorithms: the node before j on the path
+/// from i to j together with the number of edges on that path
+#[derive(Clone, Copy, Debug)]
+pub struct PathStep {
+    pub predecessor: usize,
+    pub hops: usize,
+}
+
+/// Path matrix, None means that there is no path or that i == j
+pub type PathMatrix = Matrix<Option<PathStep>>;
 
-    floyd_warshall_base!(result, matrix_b, result, b);
+// The number of edges of a path, the path from a node to itself has no edges
+fn hop_count(step: Option<PathStep>) -> usize {
+    step.map_or(0, |step| step.hops)
 }
 
-// FW in place where the output matrix, and matrix A and B are different.
-pub fn floyd_warshall_in_place4<W: Num + Copy + PartialOrd + Debug + Clone>(
-    matrix_c: &Vec<Vec<Option<W>>>,
-    matrix_a: &[Vec<Option<W>>],
-    matrix_b: &[&[Option<W>]],
-    b: usize,
-) -> Vec<Vec<Option<W>>> {
-    let mut result = matrix_c.to_owned();
+/// Build the starting path matrix: i is the predecessor of j if there is an edge i -> j
+pub fn initial_paths(predecessors: &PredecessorMatrix) -> PathMatrix {
+    predecessors.map(|predecessor| predecessor.map(|predecessor| PathStep { predecessor, hops: 1 }))
+}
+
+/// Drop the hop counts to end up with the predecessor matrix
+pub fn paths_to_predecessors(paths: &PathMatrix) -> PredecessorMatrix {
+    paths.map(|step| step.map(|step| step.predecessor))
+}
 
-    floyd_warshall_base!(matrix_a, matrix_b, result, b);
+/// Path tracking versions of the functions above. Next to the distance blocks they take the
+/// path blocks of the same matrices and also update the paths of the output block
+pub fn floyd_warshall_in_place1_paths<W: Weight>(matrix_c: &mut Matrix<W>, path_c: &mut PathMatrix) {
+    floyd_warshall_base!(matrix_c, matrix_c, matrix_c, path_c, path_c, path_c);
 }
 
-pub fn slice_matrix_immut<W: Clone>(
-    data: &[Vec<Option<W>>],
+pub fn floyd_warshall_in_place2_paths<W: Weight>(
+    matrix_c: &mut Matrix<W>,
+    path_c: &mut PathMatrix,
+    matrix_a: &Matrix<W>,
+    path_a: &PathMatrix,
+) {
+    floyd_warshall_base!(matrix_a, matrix_c, matrix_c, path_a, path_c, path_c);
+}
+
+pub fn floyd_warshall_in_place3_paths<W: Weight>(
+    matrix_c: &mut Matrix<W>,
+    path_c: &mut PathMatrix,
+    matrix_b: &Matrix<W>,
+    path_b: &PathMatrix,
+) {
+    floyd_warshall_base!(matrix_c, matrix_b, matrix_c, path_c, path_b, path_c);
+}
+
+pub fn floyd_warshall_in_place4_paths<W: Weight>(
+    matrix_c: &mut Matrix<W>,
+    path_c: &mut PathMatrix,
+    matrix_a: &Matrix<W>,
+    path_a: &PathMatrix,
+    matrix_b: &Matrix<W>,
+    path_b: &PathMatrix,
+) {
+    floyd_warshall_base!(matrix_a, matrix_b, matrix_c, path_a, path_b, path_c);
+}
+
+/// Function to copy the block (block_row, block_col) out of a matrix which is divided in blocks
+/// of (block_size x block_size). The blocks at the end are smaller if the size of the matrix is
+/// not a multiple of the block size
+pub fn slice_matrix_block<T: Clone>(
+    data: &Matrix<T>,
     block_row: usize,
     block_col: usize,
     block_size: usize,
-) -> Vec<&[Option<W>]> {
-    let row_start = block_row * block_size;
-    let row_end = (block_row + 1) * block_size;
-    let row_end = row_end.min(data.len());
-    let col_start = block_col * block_size;
-    let col_end = (block_col + 1) * block_size;
-    let col_end = col_end.min(data[0].len());
-
-    data[row_start..row_end]
-        .iter()
-        .map(|row| &row[col_start..col_end])
-        .collect()
-}
-// Function to slice the matrix into a matrix [i0..istop][j0..jstop]
-pub fn slice_matrix_block<W: Clone>(
-    data: &[Vec<Option<W>>],
+) -> Matrix<T> {
+    data.block(block_row * block_size, block_col * block_size, block_size, block_size)
+}
+
+/// Function to copy a block back to the position (block_row, block_col) of the original matrix
+pub fn write_back_to_distance<T: Clone>(
+    distance: &mut Matrix<T>,
+    block_matrix: &Matrix<T>,
     block_row: usize,
     block_col: usize,
     block_size: usize,
-) -> Vec<Vec<Option<W>>> {
-    let row_start = block_row * block_size;
-    let row_end = (block_row + 1) * block_size;
-    let col_start = block_col * block_size;
-    let col_end = (block_col + 1) * block_size;
-
-    slice_matrix(data, row_start, row_end, col_start, col_end)
-}
-
-pub fn slice_matrix<W: Clone>(
-    data: &[Vec<Option<W>>],
-    row_start: usize,
-    row_end: usize,
-    col_start: usize,
-    col_end: usize,
-) -> Vec<Vec<Option<W>>> {
-    let row_end = row_end.min(data.len());
-    let col_end = col_end.min(data[0].len());
-    let sliced_matrix: Vec<Vec<Option<W>>> = data[row_start..row_end]
-        .iter()
-        .map(|row| row[col_start..col_end].to_vec())
-        .collect();
-    sliced_matrix
-}
-
-// Function to copy the matrix back to the original matrix
-pub fn write_back_to_distance<W: Copy>(
-    distance: &mut [Vec<Option<W>>],
-    block_matrix: &[Vec<Option<W>>],
-    row_start: usize,
-    col_start: usize,
 ) {
-    let row_end = row_start + block_matrix.len();
-    let col_end = col_start + block_matrix[0].len();
-    for i in row_start..row_end {
-        for j in col_start..col_end {
-            distance[i][j] = block_matrix[i - row_start][j - col_start];
+    distance.write_block(block_row * block_size, block_col * block_size, block_matrix);
+}
+
+/// One tile of the matrix, the paths are empty if they are not tracked
+pub struct Tile<W> {
+    pub distance: Matrix<W>,
+    pub paths: PathMatrix,
+}
+
+/// The matrix split in tiles for the parallel blocked algorithms. Every round k has three phases:
+/// the diagonal tile, the other tiles in row and column k, and all remaining tiles. Within a phase
+/// every tile is either written by one thread or only read, so the locks never wait on each other
+pub struct Tiles<W> {
+    pub blocks: usize,
+    tiles: Vec<RwLock<Tile<W>>>,
+}
+
+impl<W: Weight> Tiles<W> {
+    /// Split the distance and path matrix in tiles of (block_size x block_size), the path matrix
+    /// can be left empty if the paths are not tracked
+    pub fn split(distance: &Matrix<W>, paths: &PathMatrix, block_size: usize) -> Self {
+        let blocks = distance.rows().div_ceil(block_size);
+        let mut tiles = Vec::with_capacity(blocks * blocks);
+        for i in 0..blocks {
+            for j in 0..blocks {
+                let paths = if paths.rows() > 0 {
+                    slice_matrix_block(paths, i, j, block_size)
+                } else {
+                    Matrix::default()
+                };
+                tiles.push(RwLock::new(Tile {
+                    distance: slice_matrix_block(distance, i, j, block_size),
+                    paths,
+                }));
+            }
+        }
+
+        Tiles { blocks, tiles }
+    }
+
+    pub fn lock(&self, i: usize, j: usize) -> &RwLock<Tile<W>> {
+        &self.tiles[i * self.blocks + j]
+    }
+
+    /// Copy the tiles back into the distance and path matrix
+    pub fn join(self, distance: &mut Matrix<W>, paths: &mut PathMatrix, block_size: usize) {
+        let blocks = self.blocks;
+        for (index, tile) in self.tiles.into_iter().enumerate() {
+            let tile = tile.into_inner().expect("a thread panicked");
+            let (i, j) = (index / blocks, index % blocks);
+            write_back_to_distance(distance, &tile.distance, i, j, block_size);
+            if paths.rows() > 0 {
+                write_back_to_distance(paths, &tile.paths, i, j, block_size);
+            }
+        }
+    }
+
+    /// Phase 1 of round k: FW on the diagonal tile. Returns true if a distance on the diagonal
+    /// became negative, which means that there is a negative cycle
+    pub fn update_diagonal(&self, k: usize, track_paths: bool) -> bool {
+        let mut kk = self.lock(k, k).write().expect("a thread panicked");
+        let kk = &mut *kk;
+        if track_paths {
+            floyd_warshall_in_place1_paths(&mut kk.distance, &mut kk.paths);
+        } else {
+            floyd_warshall_in_place1(&mut kk.distance);
+        }
+        has_negative_diagonal(&kk.distance)
+    }
+
+    /// Phase 2 of round k: update tile (i, j) in row or column k, which only depends on tile (k, k)
+    pub fn update_row_column(&self, k: usize, i: usize, j: usize, track_paths: bool) {
+        let kk = self.lock(k, k).read().expect("a thread panicked");
+        let mut ij = self.lock(i, j).write().expect("a thread panicked");
+        let ij = &mut *ij;
+        match (i == k, track_paths) {
+            (true, true) => floyd_warshall_in_place2_paths(
+                &mut ij.distance,
+                &mut ij.paths,
+                &kk.distance,
+                &kk.paths,
+            ),
+            (true, false) => floyd_warshall_in_place2(&mut ij.distance, &kk.distance),
+            (false, true) => floyd_warshall_in_place3_paths(
+                &mut ij.distance,
+                &mut ij.paths,
+                &kk.distance,
+                &kk.paths,
+            ),
+            (false, false) => floyd_warshall_in_place3(&mut ij.distance, &kk.distance),
         }
     }
+
+    /// Phase 3 of round k: update tile (i, j), which depends on the tiles (i, k) and (k, j).
+    /// Returns true if a distance on the diagonal became negative
+    pub fn update_other(&self, k: usize, i: usize, j: usize, track_paths: bool) -> bool {
+        let ik = self.lock(i, k).read().expect("a thread panicked");
+        let kj = self.lock(k, j).read().expect("a thread panicked");
+        let mut ij = self.lock(i, j).write().expect("a thread panicked");
+        let ij = &mut *ij;
+        if track_paths {
+            floyd_warshall_in_place4_paths(
+                &mut ij.distance,
+                &mut ij.paths,
+                &ik.distance,
+                &ik.paths,
+                &kj.distance,
+                &kj.paths,
+            );
+        } else {
+            floyd_warshall_in_place4(&mut ij.distance, &ik.distance, &kj.distance);
+        }
+        i == j && has_negative_diagonal(&ij.distance)
+    }
+}
+
+/// The tiles updated in phase 2 of round k: row k and column k without the diagonal tile
+pub fn row_column_tiles(k: usize, blocks: usize) -> impl Iterator<Item = (usize, usize)> {
+    (0..blocks).filter(move |&b| b != k).flat_map(move |b| [(k, b), (b, k)])
+}
+
+/// The tiles updated in phase 3 of round k: all tiles outside row k and column k
+pub fn other_tiles(k: usize, blocks: usize) -> impl Iterator<Item = (usize, usize)> {
+    (0..blocks)
+        .filter(move |&i| i != k)
+        .flat_map(move |i| (0..blocks).filter(move |&j| j != k).map(move |j| (i, j)))
 }
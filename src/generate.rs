@@ -0,0 +1,626 @@
+// Random graph generators for the benchmark instances
+// The structure and the weights are drawn from two streams of the same seeded generator, such that
+// a seed gives the same edges for every weight type and weight distribution
+// 30-01-2024
+
+// Import crates
+use num::NumCast;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use rand_chacha::rand_core::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use rand_distr::{Distribution, Exp, Normal, Uniform};
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::str::FromStr;
+
+// Import crates from our files
+use crate::export::{write_graph, ExportOptions};
+use crate::graph::EdgeList;
+use crate::loader::{FromGraphFile, GraphFormat};
+use crate::weight::Weight;
+
+/// The random graph models
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Model {
+    /// Erdős–Rényi graph, every pair of nodes has an edge with the probability
+    Gnp { nodes: usize, probability: f64 },
+    /// Erdős–Rényi graph with exactly this number of edges (at most one per pair), chosen
+    /// uniformly
+    Gnm { nodes: usize, edges: usize },
+    /// The nodes are random points in the unit square, connected if they are at most the radius
+    /// apart
+    Geometric { nodes: usize, radius: f64 },
+    /// Barabási–Albert preferential attachment, every new node is connected to this number of
+    /// existing nodes, chosen with a probability proportional to their degree
+    BarabasiAlbert { nodes: usize, edges_per_node: usize },
+    /// A rows x columns grid in which every node is connected to its horizontal and vertical
+    /// neighbours. Every street is kept with the probability, below 1 this looks like a road
+    /// network
+    Grid { rows: usize, columns: usize, keep: f64 },
+    /// An edge between every pair of nodes
+    Complete { nodes: usize },
+}
+
+impl Model {
+    pub fn node_count(&self) -> usize {
+        match *self {
+            Model::Gnp { nodes, .. }
+            | Model::Gnm { nodes, .. }
+            | Model::Geometric { nodes, .. }
+            | Model::BarabasiAlbert { nodes, .. }
+            | Model::Complete { nodes } => nodes,
+            Model::Grid { rows, columns, .. } => rows * columns,
+        }
+    }
+
+    /// Check the parameters, an error describes the first one that is not valid
+    pub fn validate(&self) -> Result<(), String> {
+        match *self {
+            Model::Gnp { probability, .. } if !(0.0..=1.0).contains(&probability) => {
+                Err(format!("the probability {} is not between 0 and 1", probability))
+            }
+            Model::Geometric { radius, .. } if radius.is_nan() || radius < 0.0 => {
+                Err(format!("the radius {} is negative", radius))
+            }
+            Model::BarabasiAlbert { nodes, edges_per_node }
+                if edges_per_node == 0 || edges_per_node >= nodes =>
+            {
+                Err(format!(
+                    "the edges per node must be between 1 and the number of nodes ({}), not {}",
+                    nodes, edges_per_node
+                ))
+            }
+            Model::Grid { keep, .. } if !(0.0..=1.0).contains(&keep) => {
+                Err(format!("the probability {} is not between 0 and 1", keep))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The distribution of the edge weights. The integer weight types get rounded values, and values
+/// above the largest weight of the type are clamped to it. A distribution which can draw negative
+/// weights is refused for the unsigned types, see [`GraphGenerator::validate`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WeightDistribution {
+    /// Every edge has the same weight
+    Constant(f64),
+    /// Uniform between low and high (both included), the integers in between for the integer
+    /// types
+    Uniform { low: f64, high: f64 },
+    Normal { mean: f64, std: f64 },
+    Exponential { mean: f64 },
+}
+
+impl WeightDistribution {
+    /// Check the parameters, an error describes the first one that is not valid
+    pub fn validate(&self) -> Result<(), String> {
+        // An infinite or NaN parameter gives no finite weights to draw
+        let parameters = match *self {
+            WeightDistribution::Constant(weight) => vec![weight],
+            WeightDistribution::Uniform { low, high } => vec![low, high],
+            WeightDistribution::Normal { mean, std } => vec![mean, std],
+            WeightDistribution::Exponential { mean } => vec![mean],
+        };
+        if let Some(parameter) = parameters.iter().find(|parameter| !parameter.is_finite()) {
+            return Err(format!("the parameter {} is not a finite number", parameter));
+        }
+
+        match *self {
+            WeightDistribution::Uniform { low, high } if low > high => {
+                Err(format!("the uniform range {}..{} is empty", low, high))
+            }
+            WeightDistribution::Normal { std, .. } if std < 0.0 => {
+                Err(format!("the standard deviation {} is negative", std))
+            }
+            WeightDistribution::Exponential { mean } if mean <= 0.0 => {
+                Err(format!("the mean {} of an exponential distribution is not positive", mean))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// True if a weight can be negative. Any normal distribution with a spread can be
+    pub fn can_be_negative(&self) -> bool {
+        match *self {
+            WeightDistribution::Constant(weight) => weight < 0.0,
+            WeightDistribution::Uniform { low, .. } => low < 0.0,
+            WeightDistribution::Normal { mean, std } => mean < 0.0 || std > 0.0,
+            WeightDistribution::Exponential { .. } => false,
+        }
+    }
+}
+
+/// Parse a distribution as `constant:<weight>`, `uniform:<low>:<high>`, `normal:<mean>:<std>` or
+/// `exponential:<mean>`
+impl FromStr for WeightDistribution {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut parts = text.split(':');
+        let name = parts.next().unwrap_or_default();
+        let values = parts
+            .map(|value| {
+                let number = value.parse::<f64>();
+                number.map_err(|_| format!("\"{}\" is not a number", value))
+            })
+            .collect::<Result<Vec<f64>, String>>()?;
+
+        let distribution = match (name, values.as_slice()) {
+            ("constant", &[weight]) => WeightDistribution::Constant(weight),
+            ("uniform", &[low, high]) => WeightDistribution::Uniform { low, high },
+            ("normal", &[mean, std]) => WeightDistribution::Normal { mean, std },
+            ("exponential", &[mean]) => WeightDistribution::Exponential { mean },
+            _ => {
+                return Err(format!(
+                    "\"{}\" is not constant:<weight>, uniform:<low>:<high>, normal:<mean>:<std> \
+                     or exponential:<mean>",
+                    text
+                ))
+            }
+        };
+        distribution.validate()?;
+        Ok(distribution)
+    }
+}
+
+/// Generates random graphs of a model. Without a seed the generator uses seed 0, so every run
+/// gives the same graph
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GraphGenerator {
+    pub model: Model,
+    /// If false every edge is added in both directions with the same weight. A directed G(n, p)
+    /// or G(n, m) graph draws the arcs independently, the other models get both arcs with
+    /// independent weights
+    pub directed: bool,
+    pub weights: WeightDistribution,
+    pub seed: u64,
+}
+
+impl GraphGenerator {
+    /// An undirected graph with weights uniform between 0 and 100, as the instances in the
+    /// repository
+    pub fn new(model: Model) -> Self {
+        GraphGenerator {
+            model,
+            directed: false,
+            weights: WeightDistribution::Uniform {
+                low: 0.0,
+                high: 100.0,
+            },
+            seed: 0,
+        }
+    }
+
+    pub fn directed(mut self, directed: bool) -> Self {
+        self.directed = directed;
+        self
+    }
+
+    pub fn with_weights(mut self, weights: WeightDistribution) -> Self {
+        self.weights = weights;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Check the parameters of the model and the weights for the weight type W. The unsigned types
+    /// can't hold a negative weight
+    pub fn validate<W: Weight + NumCast>(&self) -> Result<(), String> {
+        self.model.validate()?;
+        if let Model::Gnm { nodes, edges } = self.model {
+            let pairs = pair_count(nodes, self.directed);
+            if edges > pairs {
+                return Err(format!("{} nodes have at most {} edges, not {}", nodes, pairs, edges));
+            }
+        }
+        self.weights.validate()?;
+        if self.weights.can_be_negative() && W::from(-1.0).is_none() {
+            return Err("the weights can be negative, use a signed weight type".to_string());
+        }
+        Ok(())
+    }
+
+    /// Generate the edges of a graph, an undirected edge in both directions. Fails if the
+    /// parameters are not valid, see [`GraphGenerator::validate`]
+    pub fn generate<W: Weight + NumCast>(&self) -> Result<EdgeList<W>, String> {
+        self.validate::<W>()?;
+        let mut builder = Builder::<W>::new(self);
+        match self.model {
+            Model::Gnp { nodes, probability } => builder.gnp(nodes, probability),
+            Model::Gnm { nodes, edges } => builder.gnm(nodes, edges),
+            Model::Geometric { nodes, radius } => builder.geometric(nodes, radius),
+            Model::BarabasiAlbert { nodes, edges_per_node } => {
+                builder.barabasi_albert(nodes, edges_per_node)
+            }
+            Model::Grid { rows, columns, keep } => builder.grid(rows, columns, keep),
+            Model::Complete { nodes } => builder.complete(nodes),
+        }
+        Ok(builder.graph)
+    }
+
+    /// Generate a graph of any type that can be loaded from a file, e.g. a [`crate::GraphAM`]
+    pub fn build<W, G>(&self) -> Result<G, String>
+    where
+        W: Weight + NumCast,
+        G: FromGraphFile<W>,
+    {
+        let edges = self.generate::<W>()?;
+        let mut graph = G::instantiate_graph(edges.node_count, edges.edges.len());
+        for edge in &edges.edges {
+            G::add_edge(&mut graph, edge.from, edge.to, edge.weight);
+        }
+        Ok(graph)
+    }
+
+    /// Generate a graph and write it to a file, an undirected graph as an undirected file. The
+    /// error describes invalid parameters or why the file could not be written
+    pub fn write<W>(&self, file_path: &str, format: GraphFormat) -> Result<(), String>
+    where
+        W: Weight + NumCast + Display,
+    {
+        let options = ExportOptions::new().undirected(!self.directed);
+        let edges = self.generate::<W>()?;
+        write_graph(file_path, format, &edges, &options).map_err(|error| error.to_string())
+    }
+}
+
+// The number of pairs of different nodes, ordered for a directed graph
+fn pair_count(nodes: usize, directed: bool) -> usize {
+    let ordered_pairs = nodes * nodes.saturating_sub(1);
+    if directed {
+        ordered_pairs
+    } else {
+        ordered_pairs / 2
+    }
+}
+
+// The weights of the edges, drawn from their own stream
+enum WeightSampler {
+    Constant(f64),
+    UniformInteger(Uniform<i64>),
+    UniformReal(Uniform<f64>),
+    Normal(Normal<f64>),
+    Exponential(Exp<f64>),
+}
+
+// Collects the edges of a graph while the model is generated
+struct Builder<W: Weight> {
+    graph: EdgeList<W>,
+    directed: bool,
+    rng: ChaCha8Rng,
+    weight_rng: ChaCha8Rng,
+    sampler: WeightSampler,
+    integer: bool,
+}
+
+impl<W: Weight + NumCast> Builder<W> {
+    fn new(generator: &GraphGenerator) -> Self {
+        // 1 / 2 rounds to 0 for the integer types
+        let integer = W::one() / (W::one() + W::one()) == W::zero();
+        let sampler = match generator.weights {
+            WeightDistribution::Constant(weight) => WeightSampler::Constant(weight),
+            WeightDistribution::Uniform { low, high } if integer && low.ceil() <= high.floor() => {
+                WeightSampler::UniformInteger(Uniform::new_inclusive(
+                    low.ceil() as i64,
+                    high.floor() as i64,
+                ))
+            }
+            // No integer in the range, the rounded value is the closest one
+            WeightDistribution::Uniform { low, high } => {
+                WeightSampler::UniformReal(Uniform::new_inclusive(low, high))
+            }
+            WeightDistribution::Normal { mean, std } => {
+                WeightSampler::Normal(Normal::new(mean, std).expect("validated"))
+            }
+            WeightDistribution::Exponential { mean } => {
+                WeightSampler::Exponential(Exp::new(1.0 / mean).expect("validated"))
+            }
+        };
+        let mut weight_rng = ChaCha8Rng::seed_from_u64(generator.seed);
+        weight_rng.set_stream(1);
+
+        Builder {
+            graph: EdgeList::with_capacity(generator.model.node_count(), 0),
+            directed: generator.directed,
+            rng: ChaCha8Rng::seed_from_u64(generator.seed),
+            weight_rng,
+            sampler,
+            integer,
+        }
+    }
+
+    // Draw a weight and convert it to W, clamped to the finite values of the type. A negative
+    // weight is only drawn for the signed types, see GraphGenerator::validate
+    fn weight(&mut self) -> W {
+        let rng = &mut self.weight_rng;
+        let value = match &self.sampler {
+            WeightSampler::Constant(weight) => *weight,
+            WeightSampler::UniformInteger(uniform) => uniform.sample(rng) as f64,
+            WeightSampler::UniformReal(uniform) => uniform.sample(rng),
+            WeightSampler::Normal(normal) => normal.sample(rng),
+            WeightSampler::Exponential(exp) => exp.sample(rng),
+        };
+        let value = if self.integer { value.round() } else { value };
+        // A sample outside of the real distances of the type gets the nearest one, it would be
+        // taken for a clamped sum (or no edge at all) otherwise
+        match W::from(value) {
+            Some(weight) if weight > W::MAX_FINITE => W::MAX_FINITE,
+            Some(weight) if weight < W::MIN_FINITE => W::MIN_FINITE,
+            Some(weight) => weight,
+            None if value < 0.0 => W::MIN_FINITE,
+            None => W::MAX_FINITE,
+        }
+    }
+
+    // Add an edge, an undirected one in both directions with the same weight
+    fn edge(&mut self, from: usize, to: usize) {
+        let weight = self.weight();
+        self.graph.add_edge(from, to, weight);
+        if !self.directed {
+            self.graph.add_edge(to, from, weight);
+        }
+    }
+
+    // Connect two nodes, a directed graph gets both arcs with their own weight
+    fn link(&mut self, a: usize, b: usize) {
+        self.edge(a, b);
+        if self.directed {
+            self.edge(b, a);
+        }
+    }
+
+    // The number of pairs to skip until the next edge, geometrically distributed. Bounded by the
+    // number of pairs such that a tiny probability does not overflow
+    fn skip(&mut self, log_q: f64, pairs: usize) -> usize {
+        let log_r = (1.0 - self.rng.gen::<f64>()).ln();
+        (log_r / log_q).min(pairs as f64) as usize
+    }
+
+    // Only visit the pairs which get an edge, by skipping a geometrically distributed number of
+    // pairs (Batagelj and Brandes), such that a sparse graph takes O(n + m) instead of O(n^2)
+    fn gnp(&mut self, n: usize, probability: f64) {
+        if probability <= 0.0 || n < 2 {
+            return;
+        }
+        if probability >= 1.0 {
+            return self.complete(n);
+        }
+        let log_q = (1.0 - probability).ln();
+
+        if self.directed {
+            // All pairs (v, w) with v != w, row after row. `next` is the pair after the last edge
+            let (mut v, mut next) = (0, 0);
+            while v < n {
+                let mut w = next + self.skip(log_q, n * n);
+                if v == w {
+                    w += 1;
+                }
+                while v < n && w >= n {
+                    w -= n;
+                    v += 1;
+                    if v == w {
+                        w += 1;
+                    }
+                }
+                if v < n {
+                    self.edge(v, w);
+                }
+                next = w + 1;
+            }
+        } else {
+            // All pairs (v, w) with w < v, row after row
+            let (mut v, mut next) = (1, 0);
+            while v < n {
+                let mut w = next + self.skip(log_q, n * n);
+                while v < n && w >= v {
+                    w -= v;
+                    v += 1;
+                }
+                if v < n {
+                    self.edge(v, w);
+                }
+                next = w + 1;
+            }
+        }
+    }
+
+    fn gnm(&mut self, n: usize, edges: usize) {
+        let pairs = pair_count(n, self.directed);
+
+        // Most pairs get an edge, so shuffle all of them instead of drawing until there are enough
+        if 2 * edges > pairs {
+            let mut all: Vec<(usize, usize)> = (0..n)
+                .flat_map(|v| (0..n).map(move |w| (v, w)))
+                .filter(|&(v, w)| if self.directed { v != w } else { v < w })
+                .collect();
+            let (chosen, _) = all.partial_shuffle(&mut self.rng, edges);
+            for &(v, w) in chosen.iter() {
+                self.edge(v, w);
+            }
+            return;
+        }
+
+        let mut chosen = HashSet::with_capacity(edges);
+        while chosen.len() < edges {
+            let v = self.rng.gen_range(0..n);
+            let w = self.rng.gen_range(0..n);
+            let pair = if self.directed { (v, w) } else { (v.min(w), v.max(w)) };
+            if v != w && chosen.insert(pair) {
+                self.edge(pair.0, pair.1);
+            }
+        }
+    }
+
+    fn geometric(&mut self, n: usize, radius: f64) {
+        let points: Vec<(f64, f64)> = (0..n).map(|_| (self.rng.gen(), self.rng.gen())).collect();
+        for a in 0..n {
+            for b in a + 1..n {
+                let (dx, dy) = (points[a].0 - points[b].0, points[a].1 - points[b].1);
+                if dx * dx + dy * dy <= radius * radius {
+                    self.link(a, b);
+                }
+            }
+        }
+    }
+
+    // Start with a star of edges_per_node + 1 nodes. `repeated` has every node once for each of its
+    // edges, so a uniform choice from it is proportional to the degree
+    fn barabasi_albert(&mut self, n: usize, edges_per_node: usize) {
+        let mut repeated = Vec::with_capacity(2 * n * edges_per_node);
+        for node in 1..=edges_per_node {
+            self.link(0, node);
+            repeated.extend([0, node]);
+        }
+
+        let mut targets = Vec::with_capacity(edges_per_node);
+        for source in edges_per_node + 1..n {
+            targets.clear();
+            while targets.len() < edges_per_node {
+                let target = repeated[self.rng.gen_range(0..repeated.len())];
+                if !targets.contains(&target) {
+                    targets.push(target);
+                }
+            }
+            for &target in &targets {
+                self.link(source, target);
+                repeated.extend([source, target]);
+            }
+        }
+    }
+
+    fn grid(&mut self, rows: usize, columns: usize, keep: f64) {
+        for row in 0..rows {
+            for column in 0..columns {
+                let node = row * columns + column;
+                if column + 1 < columns && self.rng.gen_bool(keep) {
+                    self.link(node, node + 1);
+                }
+                if row + 1 < rows && self.rng.gen_bool(keep) {
+                    self.link(node, node + columns);
+                }
+            }
+        }
+    }
+
+    fn complete(&mut self, n: usize) {
+        for a in 0..n {
+            for b in a + 1..n {
+                self.link(a, b);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edges<W: Weight>(graph: &EdgeList<W>) -> Vec<(usize, usize, W)> {
+        graph.edges.iter().map(|edge| (edge.from, edge.to, edge.weight)).collect()
+    }
+
+    fn pairs<W: Weight>(graph: &EdgeList<W>) -> Vec<(usize, usize)> {
+        graph.edges.iter().map(|edge| (edge.from, edge.to)).collect()
+    }
+
+    const MODELS: [Model; 6] = [
+        Model::Gnp { nodes: 50, probability: 0.1 },
+        Model::Gnm { nodes: 50, edges: 80 },
+        Model::Geometric { nodes: 50, radius: 0.2 },
+        Model::BarabasiAlbert { nodes: 50, edges_per_node: 2 },
+        Model::Grid { rows: 5, columns: 8, keep: 0.7 },
+        Model::Complete { nodes: 10 },
+    ];
+
+    #[test]
+    fn same_seed_gives_same_graph() {
+        for model in MODELS {
+            for directed in [false, true] {
+                let generator = GraphGenerator::new(model).directed(directed).with_seed(7);
+                let first = generator.generate::<u32>().unwrap();
+                let second = generator.generate::<u32>().unwrap();
+                assert_eq!(first.node_count, model.node_count());
+                assert_eq!(edges(&first), edges(&second), "{:?}", model);
+
+                // The edges do not depend on the weight type or distribution
+                let weights = WeightDistribution::Normal { mean: 0.0, std: 5.0 };
+                let other = generator.with_weights(weights).generate::<f64>().unwrap();
+                assert_eq!(pairs(&first), pairs(&other), "{:?}", model);
+            }
+        }
+
+        let model = Model::Gnp { nodes: 50, probability: 0.1 };
+        let first = GraphGenerator::new(model).with_seed(1).generate::<u32>().unwrap();
+        let second = GraphGenerator::new(model).with_seed(2).generate::<u32>().unwrap();
+        assert_ne!(edges(&first), edges(&second));
+    }
+
+    #[test]
+    fn gnm_has_exactly_m_distinct_edges() {
+        // Both the sampling of a sparse graph and the shuffle of a dense one
+        for edges_wanted in [0, 10, 40, 45] {
+            let model = Model::Gnm { nodes: 10, edges: edges_wanted };
+            let undirected = GraphGenerator::new(model).generate::<u32>().unwrap();
+            let pairs: HashSet<_> = undirected
+                .edges
+                .iter()
+                .filter(|edge| edge.from < edge.to)
+                .map(|edge| (edge.from, edge.to))
+                .collect();
+            assert_eq!(undirected.edges.len(), 2 * edges_wanted);
+            assert_eq!(pairs.len(), edges_wanted);
+
+            let directed = GraphGenerator::new(model).directed(true).generate::<u32>().unwrap();
+            let arcs: HashSet<_> = directed.edges.iter().map(|edge| (edge.from, edge.to)).collect();
+            assert_eq!(directed.edges.len(), edges_wanted);
+            assert_eq!(arcs.len(), edges_wanted);
+            assert!(arcs.iter().all(|(from, to)| from != to));
+        }
+    }
+
+    #[test]
+    fn invalid_parameters_are_returned() {
+        let too_many = GraphGenerator::new(Model::Gnm { nodes: 4, edges: 7 });
+        assert!(too_many.generate::<u32>().is_err());
+        assert!(too_many.directed(true).generate::<u32>().is_ok());
+
+        let probability = GraphGenerator::new(Model::Gnp { nodes: 4, probability: 1.5 });
+        assert!(probability.build::<u32, EdgeList<u32>>().is_err());
+
+        // A normal distribution can draw a negative weight, which only a signed type can hold
+        let normal = GraphGenerator::new(Model::Complete { nodes: 4 })
+            .with_weights(WeightDistribution::Normal { mean: 50.0, std: 10.0 });
+        assert!(normal.generate::<u16>().is_err());
+        assert!(normal.generate::<i32>().is_ok());
+        assert!(normal.generate::<f64>().is_ok());
+
+        let uniform = GraphGenerator::new(Model::Complete { nodes: 4 })
+            .with_weights(WeightDistribution::Uniform { low: -1.0, high: 1.0 });
+        assert!(uniform.generate::<u32>().is_err());
+        let negative = uniform.generate::<i16>().unwrap();
+        assert!(negative.edges.iter().all(|edge| (-1..=1).contains(&edge.weight)));
+
+        // A parameter which is not finite is refused before anything is drawn
+        let not_finite = [
+            WeightDistribution::Uniform { low: 0.0, high: f64::INFINITY },
+            WeightDistribution::Uniform { low: f64::NEG_INFINITY, high: 0.0 },
+            WeightDistribution::Normal { mean: 0.0, std: f64::INFINITY },
+            WeightDistribution::Normal { mean: f64::NAN, std: 1.0 },
+            WeightDistribution::Exponential { mean: f64::INFINITY },
+            WeightDistribution::Constant(f64::NAN),
+        ];
+        for weights in not_finite {
+            let generator = GraphGenerator::new(Model::Complete { nodes: 3 }).with_weights(weights);
+            assert!(weights.validate().is_err(), "{:?}", weights);
+            assert!(generator.generate::<f64>().is_err(), "{:?}", weights);
+            assert!(generator.generate::<u16>().is_err(), "{:?}", weights);
+        }
+        assert!("uniform:0:inf".parse::<WeightDistribution>().is_err());
+        assert!("constant:nan".parse::<WeightDistribution>().is_err());
+    }
+}
@@ -6,7 +6,10 @@ use petgraph::{
     Graph,
 };
 
-// Implementation of Graph with Adjacency Matrix (Dense)
+use crate::matrix::Matrix;
+use crate::weight::Weight;
+
+/// Implementation of Graph with Adjacency Matrix (Dense)
 pub struct GraphAM<W>
 where
     W: Num + Copy,
@@ -52,9 +55,49 @@ impl<W: Num + Copy> GraphAM<W> {
     // pub fn set_edge(&mut self, from: usize, to: usize, weight: Option<W>) {
     //     self.adjacency_matrix[from][to] = weight
     // }
+}
 
+impl<W: Weight> GraphAM<W> {
+    /// The adjacency matrix as a flat distance matrix, with INFINITY where there is no edge
+    pub fn distance_matrix(&self) -> Matrix<W> {
+        let mut distance = Matrix::new(self.node_count, self.node_count, W::INFINITY);
+        for (from, row) in self.adjacency_matrix.iter().enumerate() {
+            for (to, weight) in row.iter().enumerate() {
+                if let Some(w) = weight {
+                    distance[(from, to)] = *w;
+                }
+            }
+        }
+        distance
+    }
+}
+
+impl<W: Num + Copy> Default for GraphAM<W> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<W: Num + Copy + PartialOrd> GraphAM<W> {
+    /// Add the edge from -> to. The matrix has room for one edge per pair, so of parallel edges
+    /// only the lightest one is kept, as it is the only one a shortest path can use
     pub fn add_edge(&mut self, from: usize, to: usize, weight: W) {
-        self.adjacency_matrix[from][to] = Some(weight);
+        let entry = &mut self.adjacency_matrix[from][to];
+        if !matches!(*entry, Some(old) if old <= weight) {
+            *entry = Some(weight);
+        }
+    }
+
+    /// Return the first edge with a negative weight, if there is any
+    pub fn negative_edge(&self) -> Option<(usize, usize)> {
+        for (from, row) in self.adjacency_matrix.iter().enumerate() {
+            for (to, weight) in row.iter().enumerate() {
+                if matches!(weight, Some(w) if *w < W::zero()) {
+                    return Some((from, to));
+                }
+            }
+        }
+        None
     }
 }
 
@@ -68,128 +111,440 @@ where
     pub weight: W,
 }
 
-// Implementation of Graph with Adjacency List (Sparse)
-// pub struct GraphAL<W>
-// where
-//     W: Num + Copy,
-// {
-//     pub node_count: usize,
-//     pub edges: Vec<Vec<Edge<W>>>,
-// }
-
-// impl<W: Num + Copy> GraphAL<W> {
-//     pub fn new() -> Self {
-//         GraphAL {
-//             node_count: 0,
-//             edges: Vec::new(),
-//         }
-//     }
-
-//     pub fn with_capacity(nodes: usize) -> Self {
-//         let edges: Vec<Vec<Edge<W>>> = vec![Vec::new(); nodes];
-//         GraphAL {
-//             node_count: 0,
-//             edges,
-//         }
-//     }
-
-//     pub fn add_edge(&mut self, from: usize, to: usize, weight: W) {
-//         assert!(
-//             from < self.node_count && to < self.node_count,
-//             "node indices exceed graph capacity!"
-//         );
-//         self.edges[from].push(Edge { from, to, weight });
-//     }
-
-//     pub fn get_neighbors(&mut self, node: usize) -> &[Edge<W>] {
-//         &self.edges[node]
-//     }
-
-//     pub fn get_neighbors_mut(&mut self, node: usize) -> &mut [Edge<W>] {
-//         &mut self.edges[node]
-//     }
-// }
+/// Plain list of edges, used to collect the edges of a sparse file before building a
+/// [`GraphCSR`] out of them
+pub struct EdgeList<W>
+where
+    W: Num + Copy,
+{
+    pub node_count: usize,
+    pub edges: Vec<Edge<W>>,
+}
+
+impl<W: Num + Copy> EdgeList<W> {
+    pub fn with_capacity(node_count: usize, edge_count: usize) -> Self {
+        EdgeList {
+            node_count,
+            edges: Vec::with_capacity(edge_count),
+        }
+    }
 
+    pub fn add_edge(&mut self, from: usize, to: usize, weight: W) {
+        self.edges.push(Edge { from, to, weight });
+    }
+}
+
+/// Implementation of Graph in Compressed Sparse Row format (Sparse). The out-edges of node v are
+/// stored in `targets[offsets[v]..offsets[v + 1]]` with the weights at the same positions in
+/// `weights`, so the memory use is O(n + m) instead of O(n^2)
+pub struct GraphCSR<W>
+where
+    W: Num + Copy,
+{
+    pub node_count: usize,
+    pub offsets: Vec<usize>,
+    pub targets: Vec<usize>,
+    pub weights: Vec<W>,
+}
+
+impl<W: Num + Copy> GraphCSR<W> {
+    pub fn new() -> Self {
+        GraphCSR {
+            node_count: 0,
+            offsets: vec![0],
+            targets: Vec::new(),
+            weights: Vec::new(),
+        }
+    }
+
+    /// Graph with node_count nodes and no edges
+    pub fn with_capacity(node_count: usize, edge_count: usize) -> Self {
+        GraphCSR {
+            node_count,
+            offsets: vec![0; node_count + 1],
+            targets: Vec::with_capacity(edge_count),
+            weights: Vec::with_capacity(edge_count),
+        }
+    }
+
+    /// Build the graph from a list of edges in O(n + m) with a counting sort on the start node.
+    /// The edges of a node keep the order of the list
+    pub fn from_edges(node_count: usize, edges: &[Edge<W>]) -> Self {
+        // Count the out-degree of every node and turn it into the start offsets
+        let mut offsets = vec![0; node_count + 1];
+        for edge in edges {
+            offsets[edge.from + 1] += 1;
+        }
+        for node in 0..node_count {
+            offsets[node + 1] += offsets[node];
+        }
+
+        // Put every edge at the next free position of its start node
+        let mut next = offsets.clone();
+        let mut targets = vec![0; edges.len()];
+        let mut weights = vec![W::zero(); edges.len()];
+        for edge in edges {
+            let position = next[edge.from];
+            targets[position] = edge.to;
+            weights[position] = edge.weight;
+            next[edge.from] += 1;
+        }
+
+        GraphCSR {
+            node_count,
+            offsets,
+            targets,
+            weights,
+        }
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.targets.len()
+    }
+
+    pub fn out_degree(&self, node: usize) -> usize {
+        self.offsets[node + 1] - self.offsets[node]
+    }
+
+    /// Iterate over the out-edges of node as (neighbour, weight)
+    pub fn neighbours(&self, node: usize) -> impl Iterator<Item = (usize, W)> + '_ {
+        let range = self.offsets[node]..self.offsets[node + 1];
+        self.targets[range.clone()]
+            .iter()
+            .copied()
+            .zip(self.weights[range].iter().copied())
+    }
+
+    /// Insert a single edge. This moves all edges of the nodes after from, so building the graph
+    /// with [`GraphCSR::from_edges`] is a lot faster
+    pub fn add_edge(&mut self, from: usize, to: usize, weight: W) {
+        let position = self.offsets[from + 1];
+        self.targets.insert(position, to);
+        self.weights.insert(position, weight);
+        for offset in &mut self.offsets[from + 1..] {
+            *offset += 1;
+        }
+    }
+}
+
+impl<W: Num + Copy> Default for GraphCSR<W> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<W: Num + Copy> From<EdgeList<W>> for GraphCSR<W> {
+    fn from(list: EdgeList<W>) -> Self {
+        GraphCSR::from_edges(list.node_count, &list.edges)
+    }
+}
+
+// Only the entries of the adjacency matrix with an edge end up in the CSR graph
+impl<W: Num + Copy> From<&GraphAM<W>> for GraphCSR<W> {
+    fn from(graph: &GraphAM<W>) -> Self {
+        let mut csr = GraphCSR::with_capacity(graph.node_count, 0);
+        for (from, row) in graph.adjacency_matrix.iter().enumerate() {
+            for (to, weight) in row.iter().enumerate() {
+                if let Some(w) = weight {
+                    csr.targets.push(to);
+                    csr.weights.push(*w);
+                }
+            }
+            csr.offsets[from + 1] = csr.targets.len();
+        }
+        csr
+    }
+}
+
+impl<W: Num + Copy + PartialOrd> GraphCSR<W> {
+    /// Return the first edge with a negative weight, if there is any
+    pub fn negative_edge(&self) -> Option<(usize, usize)> {
+        (0..self.node_count).find_map(|from| {
+            self.neighbours(from)
+                .find(|(_, weight)| *weight < W::zero())
+                .map(|(to, _)| (from, to))
+        })
+    }
+}
+
+/// Add edges and translate between our node ids and the node indices of a petgraph graph.
+/// The node weight of the petgraph graphs is the id of the node
 pub trait MutByNodeId<W, I> {
     fn add_edge_by_ids(&mut self, from: usize, to: usize, weight: W);
     fn get_node_by_id(&self, node: usize) -> Option<I>;
     fn get_node_id(&self, node: I) -> Option<usize>;
 }
 
-impl<W: Num> MutByNodeId<W, matrix_graph::NodeIndex> for MatrixGraph<usize, W> {
+// Without a map the plain petgraph graphs have to scan all nodes to find an id, which makes
+// loading a graph O(n m). Use a [`NodeIdGraph`] instead, the index to id direction is a lookup of
+// the node weight
+impl<W: Num + PartialOrd> MutByNodeId<W, matrix_graph::NodeIndex> for MatrixGraph<usize, W> {
     fn add_edge_by_ids(&mut self, from: usize, to: usize, weight: W) {
-        let mut from_node: Option<matrix_graph::NodeIndex> = None;
-        let mut to_node: Option<matrix_graph::NodeIndex> = None;
+        let from_node = self.get_node_by_id(from).unwrap_or_else(|| self.add_node(from));
+        let to_node = self.get_node_by_id(to).unwrap_or_else(|| self.add_node(to));
 
-        for (node, node_id) in self.node_references() {
-            if *node_id == from {
-                from_node = Some(node);
-            }
-            if *node_id == to {
-                to_node = Some(node);
-            }
+        add_lightest_matrix_edge(self, from_node, to_node, weight);
+    }
+
+    fn get_node_by_id(&self, target: usize) -> Option<matrix_graph::NodeIndex> {
+        self.node_references()
+            .find(|(_, node_id)| **node_id == target)
+            .map(|(node, _)| node)
+    }
+
+    fn get_node_id(&self, target: matrix_graph::NodeIndex) -> Option<usize> {
+        if target.index() < self.node_count() {
+            Some(*self.node_weight(target))
+        } else {
+            None
         }
+    }
+}
 
-        let from_node = from_node.unwrap_or_else(|| self.add_node(from));
-        let to_node = to_node.unwrap_or_else(|| self.add_node(to));
+impl<W: Num + PartialOrd> MutByNodeId<W, graph::NodeIndex> for Graph<usize, W> {
+    fn add_edge_by_ids(&mut self, from: usize, to: usize, weight: W) {
+        let from_node = self.get_node_by_id(from).unwrap_or_else(|| self.add_node(from));
+        let to_node = self.get_node_by_id(to).unwrap_or_else(|| self.add_node(to));
 
-        self.add_edge(from_node, to_node, weight);
+        add_lightest_edge(self, from_node, to_node, weight);
     }
 
-    fn get_node_by_id(&self, target: usize) -> Option<matrix_graph::NodeIndex> {
-        for (node, node_id) in self.node_references() {
-            if *node_id == target {
-                return Some(node);
-            }
+    fn get_node_by_id(&self, target: usize) -> Option<graph::NodeIndex> {
+        self.node_references()
+            .find(|(_, node_id)| **node_id == target)
+            .map(|(node, _)| node)
+    }
+
+    fn get_node_id(&self, target: graph::NodeIndex) -> Option<usize> {
+        self.node_weight(target).copied()
+    }
+}
+
+// petgraph's Floyd-Warshall takes the weight of the last of parallel edges, so keep a single edge
+// with the lightest weight, the same as GraphAM
+fn add_lightest_edge<W: PartialOrd>(
+    graph: &mut Graph<usize, W>,
+    from: graph::NodeIndex,
+    to: graph::NodeIndex,
+    weight: W,
+) {
+    match graph.find_edge(from, to) {
+        Some(edge) if weight < graph[edge] => graph[edge] = weight,
+        Some(_) => {}
+        None => {
+            graph.add_edge(from, to, weight);
         }
-        None
     }
+}
 
-    fn get_node_id(&self, target: matrix_graph::NodeIndex) -> Option<usize> {
-        for (node, node_id) in self.node_references() {
-            if node == target {
-                return Some(*node_id);
+// petgraph's MatrixGraph panics when an edge is added twice, so a parallel edge only lowers the
+// weight of the edge that is already there
+fn add_lightest_matrix_edge<W: PartialOrd>(
+    graph: &mut MatrixGraph<usize, W>,
+    from: matrix_graph::NodeIndex,
+    to: matrix_graph::NodeIndex,
+    weight: W,
+) {
+    if !graph.has_edge(from, to) {
+        graph.add_edge(from, to, weight);
+        return;
+    }
+    let old = graph.edge_weight_mut(from, to);
+    if weight < *old {
+        *old = weight;
+    }
+}
+
+/// A petgraph graph together with the maps between our node ids and its node indices, such that
+/// adding an edge and translating a node take constant time
+pub struct NodeIdGraph<G, I> {
+    pub graph: G,
+    // `indices[id]` is the index of the node with this id, if it was added
+    indices: Vec<Option<I>>,
+    // `ids[index]` is the id of the node at this index
+    ids: Vec<usize>,
+}
+
+/// A petgraph [`Graph`] with the id maps
+pub type IdGraph<W> = NodeIdGraph<Graph<usize, W>, graph::NodeIndex>;
+/// A petgraph [`MatrixGraph`] with the id maps
+pub type IdMatrixGraph<W> = NodeIdGraph<MatrixGraph<usize, W>, matrix_graph::NodeIndex>;
+
+impl<G, I: Copy> NodeIdGraph<G, I> {
+    pub fn node_count(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// The node ids, in the order of the node indices
+    pub fn ids(&self) -> &[usize] {
+        &self.ids
+    }
+
+    /// Unwrap the petgraph graph
+    pub fn into_graph(self) -> G {
+        self.graph
+    }
+
+    // Return the index of the id, `add_node` adds the node to the graph if it is new
+    fn index_or_add(&mut self, id: usize, add_node: impl FnOnce(&mut G, usize) -> I) -> I {
+        if id >= self.indices.len() {
+            self.indices.resize(id + 1, None);
+        }
+        match self.indices[id] {
+            Some(index) => index,
+            None => {
+                let index = add_node(&mut self.graph, id);
+                self.indices[id] = Some(index);
+                self.ids.push(id);
+                index
             }
         }
-        None
+    }
+
+    fn lookup_index(&self, id: usize) -> Option<I> {
+        self.indices.get(id).copied().flatten()
+    }
+}
+
+impl<W> IdGraph<W> {
+    pub fn new() -> Self {
+        Self::with_capacity(0, 0)
+    }
+
+    pub fn with_capacity(nodes: usize, edges: usize) -> Self {
+        NodeIdGraph {
+            graph: Graph::with_capacity(nodes, edges),
+            indices: Vec::with_capacity(nodes),
+            ids: Vec::with_capacity(nodes),
+        }
+    }
+
+    /// Return the index of the node with this id, the node is added if it is new
+    pub fn add_node(&mut self, id: usize) -> graph::NodeIndex {
+        self.index_or_add(id, Graph::add_node)
+    }
+}
+
+impl<W> Default for IdGraph<W> {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
-impl<W: Num> MutByNodeId<W, graph::NodeIndex> for Graph<usize, W> {
+impl<W: Num + PartialOrd> MutByNodeId<W, graph::NodeIndex> for IdGraph<W> {
     fn add_edge_by_ids(&mut self, from: usize, to: usize, weight: W) {
-        let mut from_node: Option<graph::NodeIndex> = None;
-        let mut to_node: Option<graph::NodeIndex> = None;
+        let from_node = self.add_node(from);
+        let to_node = self.add_node(to);
 
-        for (node, node_id) in self.node_references() {
-            if *node_id == from {
-                from_node = Some(node);
-            }
-            if *node_id == to {
-                to_node = Some(node);
-            }
+        add_lightest_edge(&mut self.graph, from_node, to_node, weight);
+    }
+
+    fn get_node_by_id(&self, node: usize) -> Option<graph::NodeIndex> {
+        self.lookup_index(node)
+    }
+
+    fn get_node_id(&self, node: graph::NodeIndex) -> Option<usize> {
+        self.ids.get(node.index()).copied()
+    }
+}
+
+impl<W> IdMatrixGraph<W> {
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    pub fn with_capacity(nodes: usize) -> Self {
+        NodeIdGraph {
+            graph: MatrixGraph::with_capacity(nodes),
+            indices: Vec::with_capacity(nodes),
+            ids: Vec::with_capacity(nodes),
         }
+    }
 
-        let from_node = from_node.unwrap_or_else(|| self.add_node(from));
-        let to_node = to_node.unwrap_or_else(|| self.add_node(to));
+    /// Return the index of the node with this id, the node is added if it is new
+    pub fn add_node(&mut self, id: usize) -> matrix_graph::NodeIndex {
+        self.index_or_add(id, MatrixGraph::add_node)
+    }
+}
 
-        self.add_edge(from_node, to_node, weight);
+impl<W> Default for IdMatrixGraph<W> {
+    fn default() -> Self {
+        Self::new()
     }
+}
 
-    fn get_node_by_id(&self, target: usize) -> Option<graph::NodeIndex> {
-        for (node, node_id) in self.node_references() {
-            if *node_id == target {
-                return Some(node);
+impl<W: Num + PartialOrd> MutByNodeId<W, matrix_graph::NodeIndex> for IdMatrixGraph<W> {
+    fn add_edge_by_ids(&mut self, from: usize, to: usize, weight: W) {
+        let from_node = self.add_node(from);
+        let to_node = self.add_node(to);
+
+        add_lightest_matrix_edge(&mut self.graph, from_node, to_node, weight);
+    }
+
+    fn get_node_by_id(&self, node: usize) -> Option<matrix_graph::NodeIndex> {
+        self.lookup_index(node)
+    }
+
+    fn get_node_id(&self, node: matrix_graph::NodeIndex) -> Option<usize> {
+        self.ids.get(node.index()).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::example::BaseLineFloydWarshall;
+    use crate::base::APSPAlgorithm;
+    use crate::loader::FromGraphFile;
+    use crate::testing::TempFile;
+
+    // Two parallel edges 0 -> 1 in both orders of their weights, and an edge 1 -> 2
+    const EDGE_ORDERS: [[(usize, usize, u32); 3]; 2] =
+        [[(0, 1, 3), (0, 1, 5), (1, 2, 1)], [(0, 1, 5), (0, 1, 3), (1, 2, 1)]];
+
+    #[test]
+    fn parallel_edges_keep_the_lightest_weight() {
+        for edges in EDGE_ORDERS {
+            let mut matrix = GraphAM::with_capacity(3);
+            let mut petgraph = IdGraph::with_capacity(3, 3);
+            for &(from, to, weight) in &edges {
+                matrix.add_edge(from, to, weight);
+                petgraph.add_edge_by_ids(from, to, weight);
             }
+            assert_eq!(matrix.adjacency_matrix[0][1], Some(3));
+            assert_eq!(petgraph.graph.edge_count(), 2);
+
+            let mut baseline = BaseLineFloydWarshall::new();
+            baseline.graph = petgraph.into_graph();
+            baseline.execute().unwrap();
+            let result = baseline.get_result();
+            assert_eq!(result.get(0, 1), Some(3));
+            assert_eq!(result.get(0, 2), Some(4));
         }
-        None
     }
 
-    fn get_node_id(&self, target: graph::NodeIndex) -> Option<usize> {
-        for (node, node_id) in self.node_references() {
-            if node == target {
-                return Some(*node_id);
+    #[test]
+    fn parallel_edges_in_a_matrix_graph_do_not_panic() {
+        for edges in EDGE_ORDERS {
+            let mut graph = MatrixGraph::<usize, u32>::with_capacity(3);
+            for &(from, to, weight) in &edges {
+                graph.add_edge_by_ids(from, to, weight);
             }
+            let (from, to) = (graph.get_node_by_id(0).unwrap(), graph.get_node_by_id(1).unwrap());
+            assert_eq!(*graph.edge_weight(from, to), 3);
         }
-        None
+    }
+
+    #[test]
+    fn duplicate_edges_load_into_a_matrix_graph() {
+        // The undirected edges 1 - 2 and 2 - 1 are both added in the two directions
+        let file = TempFile::with_contents("dup.gph", "3 4\n1 2 5\n2 1 3\n2 3 1\n1 2 5\n");
+        let graph = IdMatrixGraph::<u32>::from_sparse_file(&file.path).unwrap();
+        let plain = MatrixGraph::<usize, u32>::from_sparse_file(&file.path);
+
+        let (first, second) = (graph.get_node_by_id(0).unwrap(), graph.get_node_by_id(1).unwrap());
+        assert_eq!(*graph.graph.edge_weight(first, second), 3);
+        assert_eq!(*graph.graph.edge_weight(second, first), 3);
+        assert_eq!(plain.unwrap().edge_count(), 4);
     }
 }
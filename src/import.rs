@@ -0,0 +1,717 @@
+// Loaders for the standard graph formats, such that the public benchmark datasets can be used
+// without converting them to the `.gph` formats first
+// Like the sparse loader they only call `instantiate_graph` and `add_edge`, so they work for
+// every graph which implements FromGraphFile, see [`crate::loader::GraphFormat`]
+// 30-01-2024
+
+// Import crates
+use std::io::{BufRead, BufReader};
+use xml::common::Position;
+use xml::reader::{EventReader, ParserConfig, XmlEvent};
+
+// Import crates from our files
+use crate::labels::Labels;
+use crate::loader::{
+    open_file, parse_node, parse_number, parse_weight, read_line, GraphFile, LoadError,
+    LoadOptions,
+};
+use crate::weight::Weight;
+
+fn invalid_header(file_path: &str, line: usize, content: &str) -> LoadError {
+    LoadError::InvalidHeader {
+        path: file_path.to_string(),
+        line,
+        content: content.to_string(),
+    }
+}
+
+fn missing_value(file_path: &str, line: usize, expected: usize, found: usize) -> LoadError {
+    LoadError::MissingValue {
+        path: file_path.to_string(),
+        line,
+        expected,
+        found,
+    }
+}
+
+/// Read a DIMACS shortest path file: comment lines `c ...`, the problem line `p sp <nodes>
+/// <arcs>` and one arc `a <from> <to> <weight>` per line, with the nodes counted from 1
+pub fn load_dimacs<W, G>(
+    file_path: &str,
+    options: &LoadOptions,
+    instantiate_graph: fn(nodes: usize, edges: usize) -> G,
+    add_edge: fn(graph: &mut G, from: usize, to: usize, weight: W),
+) -> Result<G, LoadError>
+where
+    W: Weight,
+{
+    read_dimacs(file_path, options, instantiate_graph, add_edge).map(|file| file.graph)
+}
+
+pub(crate) fn read_dimacs<W, G>(
+    file_path: &str,
+    options: &LoadOptions,
+    instantiate_graph: fn(nodes: usize, edges: usize) -> G,
+    add_edge: fn(graph: &mut G, from: usize, to: usize, weight: W),
+) -> Result<GraphFile<G>, LoadError>
+where
+    W: Weight,
+{
+    let file = open_file(file_path)?;
+    let mut lines = BufReader::new(file).lines().enumerate();
+    let directed = options.directed.unwrap_or(true);
+
+    // The graph is created at the problem line, together with its number of nodes
+    let mut graph: Option<(G, usize)> = None;
+    let mut arcs_read = 0;
+    while let Some(line) = read_line(&mut lines, file_path) {
+        let (line_number, line) = line?;
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+
+        match (tokens.first().copied(), &mut graph) {
+            // Skip empty lines and comments
+            (None, _) => continue,
+            (Some(token), _) if token.starts_with('c') => continue,
+            (Some("p"), None) => {
+                if tokens.len() != 4 || tokens[1] != "sp" {
+                    return Err(invalid_header(file_path, line_number, &line));
+                }
+                let node_count = parse_number::<usize>(tokens[2], file_path, line_number)?;
+                let arc_count = parse_number::<usize>(tokens[3], file_path, line_number)?;
+                let edge_count = if directed { arc_count } else { 2 * arc_count };
+                graph = Some((instantiate_graph(node_count, edge_count), node_count));
+            }
+            (Some("a"), Some((graph, node_count))) => {
+                if tokens.len() < 4 {
+                    return Err(missing_value(file_path, line_number, 4, tokens.len()));
+                }
+                let from = parse_node(tokens[1], 1, *node_count, file_path, line_number)?;
+                let to = parse_node(tokens[2], 1, *node_count, file_path, line_number)?;
+                let weight = parse_weight::<W>(tokens[3], file_path, line_number)?;
+
+                add_edge(graph, from, to, weight);
+                if !directed {
+                    add_edge(graph, to, from, weight);
+                }
+                arcs_read += 1;
+            }
+            // A second problem line, an arc before the problem line or an unknown line type
+            _ => {
+                return Err(LoadError::UnexpectedLine {
+                    path: file_path.to_string(),
+                    line: line_number,
+                    content: line,
+                })
+            }
+        }
+    }
+
+    match graph {
+        Some((graph, _)) => Ok(GraphFile::unlabelled(graph, arcs_read)),
+        None => Err(LoadError::MissingHeader {
+            path: file_path.to_string(),
+        }),
+    }
+}
+
+/// Read a Matrix Market file in the coordinate format. The entry `i j w` is an edge from node i
+/// to node j (counted from 1), a `pattern` matrix has no values and all its edges get weight 1.
+/// Only the lower triangle of a `symmetric` matrix is stored, so its entries are mirrored
+pub fn load_matrix_market<W, G>(
+    file_path: &str,
+    options: &LoadOptions,
+    instantiate_graph: fn(nodes: usize, edges: usize) -> G,
+    add_edge: fn(graph: &mut G, from: usize, to: usize, weight: W),
+) -> Result<G, LoadError>
+where
+    W: Weight,
+{
+    read_matrix_market(file_path, options, instantiate_graph, add_edge).map(|file| file.graph)
+}
+
+pub(crate) fn read_matrix_market<W, G>(
+    file_path: &str,
+    options: &LoadOptions,
+    instantiate_graph: fn(nodes: usize, edges: usize) -> G,
+    add_edge: fn(graph: &mut G, from: usize, to: usize, weight: W),
+) -> Result<GraphFile<G>, LoadError>
+where
+    W: Weight,
+{
+    let file = open_file(file_path)?;
+    let mut lines = BufReader::new(file).lines().enumerate();
+
+    // The banner, e.g. `%%MatrixMarket matrix coordinate real general`. Complex values, the
+    // skew-symmetric and hermitian matrices and the dense array format are not graphs
+    let (line_number, banner) = match read_line(&mut lines, file_path) {
+        Some(line) => line?,
+        None => {
+            return Err(LoadError::MissingHeader {
+                path: file_path.to_string(),
+            })
+        }
+    };
+    let tokens: Vec<String> = banner.split_whitespace().map(str::to_lowercase).collect();
+    let tokens: Vec<&str> = tokens.iter().map(String::as_str).collect();
+    let (pattern, symmetric) = match tokens[..] {
+        ["%%matrixmarket", "matrix", "coordinate", field, symmetry] => {
+            let pattern = match field {
+                "real" | "double" | "integer" => false,
+                "pattern" => true,
+                _ => return Err(invalid_header(file_path, line_number, &banner)),
+            };
+            let symmetric = match symmetry {
+                "general" => false,
+                "symmetric" => true,
+                _ => return Err(invalid_header(file_path, line_number, &banner)),
+            };
+            (pattern, symmetric)
+        }
+        _ => return Err(invalid_header(file_path, line_number, &banner)),
+    };
+    let mirrored = symmetric || options.directed == Some(false);
+
+    // The size line `<rows> <columns> <entries>` follows the comments, the matrix has to be
+    // square to be an adjacency matrix
+    let mut graph: Option<(G, usize)> = None;
+    let expected = if pattern { 2 } else { 3 };
+    let mut entries_read = 0;
+    while let Some(line) = read_line(&mut lines, file_path) {
+        let (line_number, line) = line?;
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+
+        // Skip empty lines and comments
+        if tokens.is_empty() || tokens[0].starts_with('%') {
+            continue;
+        }
+
+        let (graph, node_count) = match &mut graph {
+            Some((graph, node_count)) => (graph, *node_count),
+            None => {
+                let sizes = tokens
+                    .iter()
+                    .map(|token| parse_number::<usize>(token, file_path, line_number))
+                    .collect::<Result<Vec<usize>, LoadError>>()?;
+                if sizes.len() != 3 || sizes[0] != sizes[1] {
+                    return Err(invalid_header(file_path, line_number, &line));
+                }
+                let edge_count = if mirrored { 2 * sizes[2] } else { sizes[2] };
+                graph = Some((instantiate_graph(sizes[0], edge_count), sizes[0]));
+                continue;
+            }
+        };
+
+        if tokens.len() < expected {
+            return Err(missing_value(file_path, line_number, expected, tokens.len()));
+        }
+        let from = parse_node(tokens[0], 1, node_count, file_path, line_number)?;
+        let to = parse_node(tokens[1], 1, node_count, file_path, line_number)?;
+        let weight = if pattern {
+            W::one()
+        } else {
+            parse_weight::<W>(tokens[2], file_path, line_number)?
+        };
+
+        add_edge(graph, from, to, weight);
+        if mirrored && from != to {
+            add_edge(graph, to, from, weight);
+        }
+        entries_read += 1;
+    }
+
+    match graph {
+        Some((graph, _)) => Ok(GraphFile::unlabelled(graph, entries_read)),
+        None => Err(LoadError::MissingHeader {
+            path: file_path.to_string(),
+        }),
+    }
+}
+
+/// Read a GraphML file. The nodes are labelled by their ids, in the order in which they appear in
+/// the file. The weight of an edge is its `weight` attribute (a `<key>` with that `attr.name`),
+/// the default of the key or 1. The `edgedefault` of the graph and the `directed` attribute of
+/// the edges are used unless [`LoadOptions::directed`] is given
+pub fn load_graphml<W, G>(
+    file_path: &str,
+    options: &LoadOptions,
+    instantiate_graph: fn(nodes: usize, edges: usize) -> G,
+    add_edge: fn(graph: &mut G, from: usize, to: usize, weight: W),
+) -> Result<G, LoadError>
+where
+    W: Weight,
+{
+    read_graphml(file_path, options, instantiate_graph, add_edge).map(|file| file.graph)
+}
+
+// An edge of a GraphML file, the weight is parsed once the edge is closed
+struct GraphMLEdge {
+    from: usize,
+    to: usize,
+    directed: bool,
+    weight: Option<String>,
+    line: usize,
+}
+
+// The value of an attribute of an XML element
+fn attribute<'a>(attributes: &'a [xml::attribute::OwnedAttribute], name: &str) -> Option<&'a str> {
+    attributes
+        .iter()
+        .find(|attribute| attribute.name.local_name == name)
+        .map(|attribute| attribute.value.as_str())
+}
+
+fn required_attribute<'a>(
+    attributes: &'a [xml::attribute::OwnedAttribute],
+    element: &str,
+    name: &str,
+    file_path: &str,
+    line: usize,
+) -> Result<&'a str, LoadError> {
+    attribute(attributes, name).ok_or_else(|| LoadError::InvalidXml {
+        path: file_path.to_string(),
+        line,
+        message: format!("<{}> without the attribute \"{}\"", element, name),
+    })
+}
+
+// Read a GraphML file together with the labels of the nodes. The edges are collected first,
+// because the number of nodes is only known at the end of the file
+pub(crate) fn read_graphml<W, G>(
+    file_path: &str,
+    options: &LoadOptions,
+    instantiate_graph: fn(nodes: usize, edges: usize) -> G,
+    add_edge: fn(graph: &mut G, from: usize, to: usize, weight: W),
+) -> Result<GraphFile<G>, LoadError>
+where
+    W: Weight,
+{
+    let file = open_file(file_path)?;
+    let config = ParserConfig::new()
+        .trim_whitespace(true)
+        .cdata_to_characters(true);
+    let mut reader = EventReader::new_with_config(BufReader::new(file), config);
+
+    let mut labels = Labels::new();
+    let mut edges: Vec<GraphMLEdge> = Vec::new();
+    // The id and the default value of the key of the edge weights
+    let mut weight_key: Option<String> = None;
+    let mut default_weight: Option<String> = None;
+    let mut edge_default_directed = false;
+    // Where the text of the document currently goes to
+    let mut in_weight_key = false;
+    let mut in_weight_data = false;
+
+    loop {
+        let line = reader.position().row as usize + 1;
+        let event = reader.next().map_err(|error| LoadError::InvalidXml {
+            path: file_path.to_string(),
+            line: error.position().row as usize + 1,
+            message: error.msg().to_string(),
+        })?;
+
+        match event {
+            XmlEvent::StartElement { name, attributes, .. } => match name.local_name.as_str() {
+                "key" => {
+                    let is_weight = attribute(&attributes, "attr.name") == Some("weight")
+                        && matches!(attribute(&attributes, "for"), Some("edge") | Some("all"));
+                    if is_weight {
+                        let id = required_attribute(&attributes, "key", "id", file_path, line)?;
+                        weight_key = Some(id.to_string());
+                        in_weight_key = true;
+                    }
+                }
+                "graph" => {
+                    let edge_default = attribute(&attributes, "edgedefault");
+                    edge_default_directed = edge_default == Some("directed");
+                }
+                "node" => {
+                    let id = required_attribute(&attributes, "node", "id", file_path, line)?;
+                    labels.intern(id);
+                }
+                "edge" => {
+                    let source =
+                        required_attribute(&attributes, "edge", "source", file_path, line)?;
+                    let target =
+                        required_attribute(&attributes, "edge", "target", file_path, line)?;
+                    let directed = match attribute(&attributes, "directed") {
+                        Some(directed) => directed == "true",
+                        None => edge_default_directed,
+                    };
+                    edges.push(GraphMLEdge {
+                        from: labels.intern(source),
+                        to: labels.intern(target),
+                        directed: options.directed.unwrap_or(directed),
+                        weight: None,
+                        line,
+                    });
+                }
+                "data" => {
+                    // Only the data of an edge which is not closed yet belongs to it
+                    let open_edge = edges.last().is_some_and(|edge| edge.weight.is_none());
+                    in_weight_data = open_edge
+                        && weight_key.is_some()
+                        && attribute(&attributes, "key") == weight_key.as_deref();
+                }
+                _ => {}
+            },
+            XmlEvent::Characters(text) => {
+                if in_weight_data {
+                    if let Some(edge) = edges.last_mut() {
+                        edge.weight = Some(text);
+                    }
+                } else if in_weight_key {
+                    default_weight = Some(text);
+                }
+            }
+            XmlEvent::EndElement { name } => match name.local_name.as_str() {
+                "key" => in_weight_key = false,
+                "data" => in_weight_data = false,
+                // Mark the edge as closed, such that the data of a later element is not used
+                "edge" => {
+                    if let Some(edge) = edges.last_mut() {
+                        edge.weight.get_or_insert_with(String::new);
+                    }
+                }
+                _ => {}
+            },
+            XmlEvent::EndDocument => break,
+            _ => {}
+        }
+    }
+
+    let edges_read = edges.len();
+    let edge_count = edges.iter().map(|edge| if edge.directed { 1 } else { 2 }).sum();
+    let mut graph = instantiate_graph(labels.len(), edge_count);
+    for edge in edges {
+        // An edge without data has the default weight of the key
+        let weight = match edge.weight.as_deref().filter(|weight| !weight.is_empty()) {
+            Some(weight) => parse_weight::<W>(weight.trim(), file_path, edge.line)?,
+            None => match &default_weight {
+                Some(weight) => parse_weight::<W>(weight.trim(), file_path, edge.line)?,
+                None => W::one(),
+            },
+        };
+
+        add_edge(&mut graph, edge.from, edge.to, weight);
+        if !edge.directed {
+            add_edge(&mut graph, edge.to, edge.from, weight);
+        }
+    }
+
+    Ok(GraphFile {
+        graph,
+        labels: Some(labels),
+        edge_count: edges_read,
+    })
+}
+
+/// Read a CSV edge list with one edge `from,to[,weight]` per line, an edge without a weight gets
+/// weight 1. Without a header the options decide: the graph is undirected, the node ids start at
+/// 0 and the nodes are numbers unless [`LoadOptions`] says otherwise. A first line with known
+/// column names for the nodes (such as `source,target`), or with a node (of a graph with node
+/// ids) or a weight which is not a number, holds the column names and is skipped, as are empty
+/// lines and lines starting with `#`. The node ids can't be far above the number of edges, since
+/// every id below the largest one is a node of the graph
+pub fn load_csv<W, G>(
+    file_path: &str,
+    options: &LoadOptions,
+    instantiate_graph: fn(nodes: usize, edges: usize) -> G,
+    add_edge: fn(graph: &mut G, from: usize, to: usize, weight: W),
+) -> Result<G, LoadError>
+where
+    W: Weight,
+{
+    read_csv(file_path, options, instantiate_graph, add_edge).map(|file| file.graph)
+}
+
+// Split a CSV line at the commas. A quoted field can contain commas, and a quote is written twice
+fn split_csv_line(
+    line: &str,
+    file_path: &str,
+    line_number: usize,
+) -> Result<Vec<String>, LoadError> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut characters = line.chars().peekable();
+    while let Some(character) = characters.next() {
+        match (character, quoted) {
+            ('"', true) if characters.peek() == Some(&'"') => {
+                field.push('"');
+                characters.next();
+            }
+            ('"', _) => quoted = !quoted,
+            (',', false) => fields.push(std::mem::take(&mut field).trim().to_string()),
+            (character, _) => field.push(character),
+        }
+    }
+    if quoted {
+        return Err(LoadError::UnterminatedQuote {
+            path: file_path.to_string(),
+            line: line_number,
+        });
+    }
+    fields.push(field.trim().to_string());
+    Ok(fields)
+}
+
+// Column names for the two nodes of an edge which are used by common datasets and tools
+const NODE_COLUMNS: [&str; 12] = [
+    "source", "target", "from", "to", "src", "dst", "u", "v", "node1", "node2", "start", "end",
+];
+
+// The first line holds the column names if both nodes have a known column name, or if one of the
+// columns which should be numbers is not. The nodes of a labelled file can be any text, so only
+// the names tell them apart from a first edge without a weight
+fn is_column_names(fields: &[String], labelled: bool) -> bool {
+    let node_column = |field: &String| {
+        NODE_COLUMNS.iter().any(|name| field.eq_ignore_ascii_case(name))
+    };
+    if fields.len() >= 2 && fields[..2].iter().all(node_column) {
+        return true;
+    }
+    fields
+        .iter()
+        .take(3)
+        .enumerate()
+        .filter(|&(column, _)| !labelled || column == 2)
+        .any(|(_, field)| field.parse::<f64>().is_err())
+}
+
+// The edges of a file without a node count touch at most two nodes each. A dense graph allocates
+// room for every pair of nodes, so a largest id far above that (such as a single edge to node
+// 1000000000) fails here instead of allocating for nodes that are not in the file. Small graphs
+// are always accepted, as are ids where half of the nodes have no edge
+fn check_node_ids(
+    node_count: usize,
+    edge_count: usize,
+    largest: &(usize, String),
+    file_path: &str,
+) -> Result<(), LoadError> {
+    const ALWAYS_ACCEPTED: usize = 1024;
+    if node_count <= ALWAYS_ACCEPTED.max(edge_count.saturating_mul(4)) {
+        return Ok(());
+    }
+    let (line, token) = largest;
+    Err(LoadError::SparseNodeIds {
+        path: file_path.to_string(),
+        line: *line,
+        token: token.clone(),
+        edge_count,
+    })
+}
+
+// Parse a node id of a CSV file, the error reports the number of nodes read so far
+fn parse_csv_node(
+    token: &str,
+    offset: usize,
+    node_count: usize,
+    file_path: &str,
+    line: usize,
+) -> Result<usize, LoadError> {
+    let node = parse_number::<usize>(token, file_path, line)?;
+    node.checked_sub(offset).ok_or_else(|| LoadError::NodeOutOfRange {
+        path: file_path.to_string(),
+        line,
+        token: token.to_string(),
+        node_count,
+    })
+}
+
+// Read a CSV edge list together with the labels of a labelled file. The edges are collected
+// first, because the number of nodes is only known at the end of the file
+pub(crate) fn read_csv<W, G>(
+    file_path: &str,
+    options: &LoadOptions,
+    instantiate_graph: fn(nodes: usize, edges: usize) -> G,
+    add_edge: fn(graph: &mut G, from: usize, to: usize, weight: W),
+) -> Result<GraphFile<G>, LoadError>
+where
+    W: Weight,
+{
+    let file = open_file(file_path)?;
+    let mut lines = BufReader::new(file).lines().enumerate();
+    let directed = options.directed.unwrap_or(false);
+    let offset = if options.one_based.unwrap_or(false) { 1 } else { 0 };
+    let mut labels = if options.labelled.unwrap_or(false) {
+        Some(Labels::new())
+    } else {
+        None
+    };
+
+    let mut edges: Vec<(usize, usize, W)> = Vec::new();
+    let mut node_count = 0;
+    let mut largest = (0, String::new());
+    let mut first_line = true;
+    while let Some(line) = read_line(&mut lines, file_path) {
+        let (line_number, line) = line?;
+
+        // Skip empty lines and comments
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+        let fields = split_csv_line(&line, file_path, line_number)?;
+        if std::mem::take(&mut first_line) && is_column_names(&fields, labels.is_some()) {
+            continue;
+        }
+        if fields.len() < 2 {
+            return Err(missing_value(file_path, line_number, 2, fields.len()));
+        }
+
+        let (from, to) = match &mut labels {
+            Some(labels) => (labels.intern(&fields[0]), labels.intern(&fields[1])),
+            // There is no header with the number of nodes, only a node below the first id fails
+            None => (
+                parse_csv_node(&fields[0], offset, node_count, file_path, line_number)?,
+                parse_csv_node(&fields[1], offset, node_count, file_path, line_number)?,
+            ),
+        };
+        let weight = match fields.get(2) {
+            Some(weight) => parse_weight::<W>(weight, file_path, line_number)?,
+            None => W::one(),
+        };
+
+        // Remember where the largest node id is, such that an error can point at it
+        let largest_node = from.max(to).saturating_add(1);
+        if largest_node > node_count && labels.is_none() {
+            let token = if from >= to { &fields[0] } else { &fields[1] };
+            largest = (line_number, token.clone());
+        }
+        node_count = node_count.max(largest_node);
+        edges.push((from, to, weight));
+    }
+
+    let edges_read = edges.len();
+    check_node_ids(node_count, edges_read, &largest, file_path)?;
+    let edge_count = if directed { edges_read } else { 2 * edges_read };
+    let mut graph = instantiate_graph(node_count, edge_count);
+    for (from, to, weight) in edges {
+        add_edge(&mut graph, from, to, weight);
+        if !directed {
+            add_edge(&mut graph, to, from, weight);
+        }
+    }
+
+    Ok(GraphFile {
+        graph,
+        labels,
+        edge_count: edges_read,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::EdgeList;
+    use crate::loader::{FromGraphFile, GraphFormat};
+    use crate::testing::TempFile;
+
+    // Write the contents to a file of its own in the temporary directory and load it
+    fn load(
+        name: &str,
+        contents: &str,
+        format: GraphFormat,
+        options: &LoadOptions,
+    ) -> Result<GraphFile<EdgeList<u32>>, LoadError> {
+        let file = TempFile::with_contents(name, contents);
+        EdgeList::from_graph_file(&file.path, format, options)
+    }
+
+    fn edges(graph: &EdgeList<u32>) -> Vec<(usize, usize, u32)> {
+        let mut edges: Vec<_> =
+            graph.edges.iter().map(|edge| (edge.from, edge.to, edge.weight)).collect();
+        edges.sort();
+        edges
+    }
+
+    #[test]
+    fn dimacs_arcs_are_directed() {
+        let contents = "c a comment\np sp 3 2\na 1 2 5\na 2 3 7\n";
+        let file = load("arcs.gr", contents, GraphFormat::Dimacs, &LoadOptions::new()).unwrap();
+        assert_eq!(file.graph.node_count, 3);
+        assert_eq!(file.edge_count, 2);
+        assert_eq!(edges(&file.graph), [(0, 1, 5), (1, 2, 7)]);
+
+        let options = LoadOptions::new().directed(false);
+        let file = load("both.gr", contents, GraphFormat::Dimacs, &options).unwrap();
+        assert_eq!(edges(&file.graph), [(0, 1, 5), (1, 0, 5), (1, 2, 7), (2, 1, 7)]);
+    }
+
+    #[test]
+    fn dimacs_arc_before_problem_line_fails() {
+        let contents = "a 1 2 5\np sp 3 1\n";
+        let result = load("early.gr", contents, GraphFormat::Dimacs, &LoadOptions::new());
+        assert!(matches!(result, Err(LoadError::UnexpectedLine { line: 1, .. })));
+    }
+
+    #[test]
+    fn matrix_market_symmetric_and_pattern() {
+        let contents = "%%MatrixMarket matrix coordinate integer symmetric\n% comment\n\
+                        3 3 2\n1 2 4\n3 2 6\n";
+        let file = load("sym.mtx", contents, GraphFormat::MatrixMarket, &LoadOptions::new());
+        let file = file.unwrap();
+        assert_eq!(file.graph.node_count, 3);
+        assert_eq!(file.edge_count, 2);
+        assert_eq!(edges(&file.graph), [(0, 1, 4), (1, 0, 4), (1, 2, 6), (2, 1, 6)]);
+
+        let contents = "%%MatrixMarket matrix coordinate pattern general\n2 2 1\n2 1\n";
+        let file = load("pattern.mtx", contents, GraphFormat::MatrixMarket, &LoadOptions::new());
+        assert_eq!(edges(&file.unwrap().graph), [(1, 0, 1)]);
+    }
+
+    #[test]
+    fn graphml_weights_and_labels() {
+        let contents = r#"<?xml version="1.0" encoding="UTF-8"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+<key id="w" for="edge" attr.name="weight" attr.type="int"><default>3</default></key>
+<graph id="G" edgedefault="directed">
+<node id="a"/>
+<node id="b"/>
+<node id="c"/>
+<edge source="a" target="b"><data key="w">8</data></edge>
+<edge source="b" target="c" directed="false"/>
+</graph>
+</graphml>
+"#;
+        let file = load("g.graphml", contents, GraphFormat::GraphML, &LoadOptions::new());
+        let file = file.unwrap();
+        assert_eq!(file.edge_count, 2);
+        assert_eq!(edges(&file.graph), [(0, 1, 8), (1, 2, 3), (2, 1, 3)]);
+        let labels = file.labels.unwrap();
+        assert_eq!(labels.index("c"), Some(2));
+        assert_eq!(labels.label(0), Some("a"));
+    }
+
+    #[test]
+    fn graphml_without_closing_tag_fails() {
+        let contents = "<graphml><graph edgedefault=\"directed\"><node id=\"a\"/>";
+        let result = load("open.graphml", contents, GraphFormat::GraphML, &LoadOptions::new());
+        assert!(matches!(result, Err(LoadError::InvalidXml { .. })));
+    }
+
+    #[test]
+    fn csv_column_names_are_skipped() {
+        let options = LoadOptions::new().directed(true);
+        let file = load("names.csv", "from,to,weight\n0,1,4\n1,2\n", GraphFormat::Csv, &options);
+        let file = file.unwrap();
+        assert_eq!(file.graph.node_count, 3);
+        assert_eq!(edges(&file.graph), [(0, 1, 4), (1, 2, 1)]);
+
+        // Without a weight column only the names tell the header apart from a labelled edge
+        let options = options.labelled(true);
+        let contents = "Source,Target\n\"Central, Station\",Airport\n";
+        let file = load("labels.csv", contents, GraphFormat::Csv, &options).unwrap();
+        assert_eq!(edges(&file.graph), [(0, 1, 1)]);
+        assert_eq!(file.labels.unwrap().label(0), Some("Central, Station"));
+    }
+
+    #[test]
+    fn csv_node_ids_far_above_the_edges_fail() {
+        let options = LoadOptions::new();
+        let result = load("far.csv", "0,1,2\n1,1000000000,3\n", GraphFormat::Csv, &options);
+        assert!(matches!(result, Err(LoadError::SparseNodeIds { line: 2, edge_count: 2, .. })));
+
+        let file = load("near.csv", "0,1,2\n1,1000,3\n", GraphFormat::Csv, &options).unwrap();
+        assert_eq!(file.graph.node_count, 1001);
+    }
+}
@@ -0,0 +1,169 @@
+// String labels for the nodes of a graph
+// The algorithms only work with the indices 0..n, the interner maps every label of a labelled
+// file to the index it got while loading and back, such that the results can be used by label
+// 30-01-2024
+
+// Import crates
+use std::collections::HashMap;
+
+// Import crates from our files
+use crate::base::APSPResult;
+use crate::weight::Weight;
+
+/// The labels of the nodes, node i has the i-th label that was added
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Labels {
+    labels: Vec<String>,
+    indices: HashMap<String, usize>,
+}
+
+impl Labels {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the index of the label, a new label gets the next index
+    pub fn intern(&mut self, label: &str) -> usize {
+        if let Some(&index) = self.indices.get(label) {
+            return index;
+        }
+        let index = self.labels.len();
+        self.labels.push(label.to_string());
+        self.indices.insert(label.to_string(), index);
+        index
+    }
+
+    /// The index of the node with this label
+    pub fn index(&self, label: &str) -> Option<usize> {
+        self.indices.get(label).copied()
+    }
+
+    /// The label of the node, None for a node without a label
+    pub fn label(&self, index: usize) -> Option<&str> {
+        self.labels.get(index).map(String::as_str)
+    }
+
+    /// The labels of the nodes on a path, e.g. from [`APSPResult::path`]. Nodes without a label
+    /// are shown by their index
+    pub fn path_labels(&self, path: &[usize]) -> Vec<String> {
+        path.iter()
+            .map(|&node| match self.label(node) {
+                Some(label) => label.to_string(),
+                None => node.to_string(),
+            })
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    /// The labels in the order of the node indices
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.labels.iter().map(String::as_str)
+    }
+}
+
+/// An [`APSPResult`] which is queried with the labels of the nodes instead of their indices
+pub struct LabelledResult<'a, W: Weight> {
+    pub result: &'a APSPResult<W>,
+    pub labels: &'a Labels,
+}
+
+impl<W: Weight> APSPResult<W> {
+    /// Look up the distances and paths by label
+    pub fn with_labels<'a>(&'a self, labels: &'a Labels) -> LabelledResult<'a, W> {
+        LabelledResult {
+            result: self,
+            labels,
+        }
+    }
+}
+
+impl<W: Weight> LabelledResult<'_, W> {
+    /// The distance between the nodes, None if there is no path or if a label is unknown
+    pub fn get(&self, from: &str, to: &str) -> Option<W> {
+        let from = self.labels.index(from)?;
+        let to = self.labels.index(to)?;
+        self.result.get(from, to)
+    }
+
+    /// The labels of the nodes on the shortest path (both included). Returns None if there is no
+    /// path, if the predecessors were not tracked or if a label is unknown
+    pub fn path(&self, from: &str, to: &str) -> Option<Vec<&str>> {
+        let from = self.labels.index(from)?;
+        let to = self.labels.index(to)?;
+        let path = self.result.path(from, to)?;
+        path.into_iter().map(|node| self.labels.label(node)).collect()
+    }
+
+    /// The labels of all nodes with a path from `from`, together with their distance
+    pub fn distances_from(&self, from: &str) -> impl Iterator<Item = (&str, W)> + '_ {
+        let row = self.labels.index(from).map(|from| self.result.row(from));
+        row.into_iter().flat_map(move |row| {
+            row.iter().enumerate().filter_map(move |(to, distance)| {
+                Some((self.labels.label(to)?, distance.finite()?))
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algorithms::floyd_warshall_am::FloydWarshall;
+    use crate::base::APSPAlgorithm;
+    use crate::loader::{LoadError, LoadOptions};
+    use crate::testing::TempFile;
+
+    #[test]
+    fn labelled_file_round_trip() {
+        let file = TempFile::with_contents(
+            "stations.gph",
+            "4 3 directed labelled\n\"Central Station\" Airport 12\nAirport 10.0.0.1 3\n\
+             \"Central Station\" Harbour 20\n",
+        );
+        let mut algorithm = FloydWarshall::<u32>::new().with_paths();
+        let labels = algorithm.load_labelled_graph(&file.path, &LoadOptions::new()).unwrap();
+        algorithm.execute().unwrap();
+        let result = algorithm.get_result();
+
+        // The nodes get their indices in the order in which the labels first appear
+        let expected = ["Central Station", "Airport", "10.0.0.1", "Harbour"];
+        assert_eq!(labels.iter().collect::<Vec<_>>(), expected);
+        for (index, label) in expected.into_iter().enumerate() {
+            assert_eq!(labels.index(label), Some(index));
+            assert_eq!(labels.label(index), Some(label));
+        }
+        assert_eq!(labels.label(4), None);
+
+        let by_label = result.with_labels(&labels);
+        assert_eq!(by_label.get("Central Station", "10.0.0.1"), Some(15));
+        assert_eq!(
+            by_label.path("Central Station", "10.0.0.1"),
+            Some(vec!["Central Station", "Airport", "10.0.0.1"])
+        );
+        // The edges are directed, and an unknown label has no distances
+        assert_eq!(by_label.get("10.0.0.1", "Airport"), None);
+        assert_eq!(by_label.path("10.0.0.1", "Airport"), None);
+        assert_eq!(by_label.get("Central Station", "Depot"), None);
+        assert_eq!(by_label.distances_from("Depot").count(), 0);
+        assert_eq!(
+            by_label.distances_from("Airport").collect::<Vec<_>>(),
+            [("Airport", 0), ("10.0.0.1", 3)]
+        );
+        assert_eq!(labels.path_labels(&[3, 5]), ["Harbour", "5"]);
+    }
+
+    #[test]
+    fn more_labels_than_nodes_are_refused() {
+        let file = TempFile::with_contents("too_many.gph", "2 2 labelled\na b 1\nb c 1\n");
+        let error = FloydWarshall::<u32>::new()
+            .load_labelled_graph(&file.path, &LoadOptions::new())
+            .unwrap_err();
+        assert!(matches!(error, LoadError::NodeOutOfRange { line: 3, node_count: 2, .. }));
+    }
+}
@@ -0,0 +1,69 @@
+//! Solvers for the all pairs shortest path (APSP) problem.
+//!
+//! Every algorithm in [`algorithms`] implements [`APSPAlgorithm`]: load a graph from a `.gph`
+//! file, execute the algorithm and collect the distances (and optionally the paths) in an
+//! [`APSPResult`].
+//!
+//! ```no_run
+//! use apsp::algorithms::floyd_warshall_am::FloydWarshall;
+//! use apsp::APSPAlgorithm;
+//!
+//! let mut algorithm = FloydWarshall::<u32>::new().with_paths();
+//! algorithm.load_graph("instances/b18.gph", true).expect("can't load the graph");
+//! algorithm.execute().expect("the graph has a negative cycle");
+//!
+//! let result = algorithm.get_result();
+//! println!("{:?}", result.get(0, 5));
+//! println!("{:?}", result.path(0, 5));
+//! ```
+
+/// The APSP algorithms, each in its own module
+pub mod algorithms;
+/// The result type and the trait shared by all algorithms
+pub mod base;
+/// Comparing the results of two algorithms
+pub mod compare;
+/// Saving and loading computed distance matrices in a binary format
+pub mod distance_file;
+/// Writing graphs and distance matrices to the graph formats, CSV and Matrix Market
+pub mod export;
+/// The block kernels of the blocked Floyd-Warshall algorithms
+pub mod fwb_function;
+/// Random graph generators
+pub mod generate;
+/// The graph representations
+pub mod graph;
+/// Reading graphs from the DIMACS, Matrix Market, GraphML and CSV formats
+pub mod import;
+/// String labels for the nodes
+pub mod labels;
+/// Reading graphs from the sparse and dense `.gph` formats
+pub mod loader;
+/// The flat row-major matrix used for the distances and predecessors
+pub mod matrix;
+/// The thread pool shared by the rayon based algorithms
+#[cfg(feature = "rayon")]
+pub mod pool;
+/// Shortest paths from a subset of the nodes
+pub mod query;
+/// Helpers shared by the unit tests
+#[cfg(test)]
+mod testing;
+/// The weight trait with the "no path" value
+pub mod weight;
+
+pub use base::{APSPAlgorithm, APSPResult, AlgorithmConfig, ExecuteError};
+pub use compare::{ComparisonReport, PairDifference};
+pub use distance_file::{
+    read_distance_file, write_distance_file, DistanceFileError, MappedDistances,
+};
+pub use export::{ExportError, ExportOptions, ToGraphFile};
+pub use generate::{GraphGenerator, Model, WeightDistribution};
+pub use graph::{GraphAM, GraphCSR, IdGraph, IdMatrixGraph, NodeIdGraph};
+pub use labels::{LabelledResult, Labels};
+pub use loader::{FromGraphFile, GraphFile, GraphFormat, LoadError, LoadOptions};
+pub use matrix::Matrix;
+#[cfg(feature = "rayon")]
+pub use pool::Pool;
+pub use query::{DistanceTable, Query, ShortestPathQuery};
+pub use weight::Weight;
@@ -1,143 +1,722 @@
 use std::{
+    error::Error,
+    fmt::{self, Display},
     fs::File,
-    io::{BufRead, BufReader},
+    io::{self, BufRead, BufReader, Lines},
+    iter::Enumerate,
     path::Path,
 };
 
-// use crate::graph::{GraphAL, GraphAM, MutByNodeId};
-use crate::graph::{GraphAM, MutByNodeId};
+use crate::graph::{
+    EdgeList, GraphAM, GraphCSR, IdGraph, IdMatrixGraph, MutByNodeId, NodeIdGraph,
+};
+use crate::import::{read_csv, read_dimacs, read_graphml, read_matrix_market};
+use crate::labels::Labels;
+use crate::weight::Weight;
 use num::Num;
 use petgraph::graph::Graph;
 use petgraph::matrix_graph::MatrixGraph;
 
+/// Errors that can occur while loading a graph file. Every variant carries the path of the file,
+/// and if the problem is on a specific line also the line number (starting at 1 for the header)
+#[derive(Debug)]
+pub enum LoadError {
+    /// The file could not be opened or read
+    Io { path: String, source: io::Error },
+    /// The file is empty, so there is no header with the graph size
+    MissingHeader { path: String },
+    /// The header does not contain the expected number of values
+    InvalidHeader { path: String, line: usize, content: String },
+    /// A token could not be parsed as a number
+    InvalidNumber { path: String, line: usize, token: String },
+    /// A weight is larger than the largest finite weight of the type, the largest integer is the
+    /// INFINITY of the algorithms and would be no edge at all, see [`Weight::MAX_FINITE`]
+    WeightTooLarge { path: String, line: usize, token: String, max: String },
+    /// A weight is smaller than the smallest finite weight of the type, see
+    /// [`Weight::MIN_FINITE`]
+    WeightTooSmall { path: String, line: usize, token: String, min: String },
+    /// A line contains less values than expected
+    MissingValue { path: String, line: usize, expected: usize, found: usize },
+    /// A node id is not in the range of the nodes given in the header, or a labelled file has more
+    /// labels than nodes
+    NodeOutOfRange { path: String, line: usize, token: String, node_count: usize },
+    /// A node id of a file without a node count is far above the number of edges, such that most
+    /// of the nodes would have no edge at all. Such ids are better loaded as labels
+    SparseNodeIds { path: String, line: usize, token: String, edge_count: usize },
+    /// A quoted label of a labelled file is not closed on the same line
+    UnterminatedQuote { path: String, line: usize },
+    /// A line of a DIMACS file is not a comment, the problem line or an arc, or an arc comes
+    /// before the problem line
+    UnexpectedLine { path: String, line: usize, content: String },
+    /// A GraphML file is not well-formed XML or misses a required attribute
+    InvalidXml { path: String, line: usize, message: String },
+    /// A row of a dense matrix does not have one column for every node
+    ColumnCount { path: String, line: usize, expected: usize, found: usize },
+    /// A dense matrix does not have one row for every node
+    RowCount { path: String, expected: usize, found: usize },
+}
+
+impl LoadError {
+    /// The path of the file that failed to load
+    pub fn path(&self) -> &str {
+        match self {
+            LoadError::Io { path, .. }
+            | LoadError::MissingHeader { path }
+            | LoadError::InvalidHeader { path, .. }
+            | LoadError::InvalidNumber { path, .. }
+            | LoadError::WeightTooLarge { path, .. }
+            | LoadError::WeightTooSmall { path, .. }
+            | LoadError::MissingValue { path, .. }
+            | LoadError::NodeOutOfRange { path, .. }
+            | LoadError::SparseNodeIds { path, .. }
+            | LoadError::UnterminatedQuote { path, .. }
+            | LoadError::UnexpectedLine { path, .. }
+            | LoadError::InvalidXml { path, .. }
+            | LoadError::ColumnCount { path, .. }
+            | LoadError::RowCount { path, .. } => path,
+        }
+    }
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Io { path, source } => write!(f, "{}: {}", path, source),
+            LoadError::MissingHeader { path } => write!(f, "{}: missing header", path),
+            LoadError::InvalidHeader { path, line, content } => {
+                write!(f, "{}:{}: invalid header \"{}\"", path, line, content)
+            }
+            LoadError::InvalidNumber { path, line, token } => {
+                write!(f, "{}:{}: can't parse \"{}\" as a number", path, line, token)
+            }
+            LoadError::WeightTooLarge { path, line, token, max } => write!(
+                f,
+                "{}:{}: the weight {} is larger than the largest weight {}",
+                path, line, token, max
+            ),
+            LoadError::WeightTooSmall { path, line, token, min } => write!(
+                f,
+                "{}:{}: the weight {} is smaller than the smallest weight {}",
+                path, line, token, min
+            ),
+            LoadError::MissingValue { path, line, expected, found } => write!(
+                f,
+                "{}:{}: expected {} values, found {}",
+                path, line, expected, found
+            ),
+            LoadError::NodeOutOfRange { path, line, token, node_count } => write!(
+                f,
+                "{}:{}: node \"{}\" is not in the graph with {} nodes",
+                path, line, token, node_count
+            ),
+            LoadError::SparseNodeIds { path, line, token, edge_count } => write!(
+                f,
+                "{}:{}: node {} is far above the number of edges ({}), load the file as labelled",
+                path, line, token, edge_count
+            ),
+            LoadError::UnterminatedQuote { path, line } => {
+                write!(f, "{}:{}: missing closing quote", path, line)
+            }
+            LoadError::UnexpectedLine { path, line, content } => {
+                write!(f, "{}:{}: unexpected line \"{}\"", path, line, content)
+            }
+            LoadError::InvalidXml { path, line, message } => {
+                write!(f, "{}:{}: {}", path, line, message)
+            }
+            LoadError::ColumnCount { path, line, expected, found } => write!(
+                f,
+                "{}:{}: expected {} columns, found {}",
+                path, line, expected, found
+            ),
+            LoadError::RowCount { path, expected, found } => {
+                write!(f, "{}: expected {} rows, found {}", path, expected, found)
+            }
+        }
+    }
+}
+
+impl Error for LoadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LoadError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// Options for loading a graph in the sparse format. Options that are None are taken from the
+/// header of the file, e.g. `5 7 directed zero-based`. If the header does not mention them
+/// either, the graph is undirected, the node ids start at 1 and the nodes are numbers. The other
+/// formats take the options they apply to, see [`GraphFormat`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LoadOptions {
+    /// If false every edge is added in both directions
+    pub directed: Option<bool>,
+    /// If true the node ids in the file start at 1, otherwise at 0
+    pub one_based: Option<bool>,
+    /// If true the nodes in the file are labels instead of numbers, see [`Labels`]
+    pub labelled: Option<bool>,
+}
+
+impl LoadOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn directed(mut self, directed: bool) -> Self {
+        self.directed = Some(directed);
+        self
+    }
+
+    pub fn one_based(mut self, one_based: bool) -> Self {
+        self.one_based = Some(one_based);
+        self
+    }
+
+    pub fn labelled(mut self, labelled: bool) -> Self {
+        self.labelled = Some(labelled);
+        self
+    }
+}
+
+/// The file formats a graph can be loaded from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// The edge list `.gph` format, see [`LoadOptions`]
+    Sparse,
+    /// The adjacency matrix `.gph` format
+    Dense,
+    /// The `.gr` format of the DIMACS shortest path challenge. The arcs are directed unless
+    /// [`LoadOptions::directed`] is false
+    Dimacs,
+    /// A Matrix Market `.mtx` file in the coordinate format. A symmetric matrix is undirected, a
+    /// general matrix is directed unless [`LoadOptions::directed`] is false
+    MatrixMarket,
+    /// A GraphML file, the nodes get their indices in the order of their ids in the file
+    GraphML,
+    /// A CSV edge list `from,to[,weight]`, see [`crate::import::load_csv`]
+    Csv,
+}
+
+impl GraphFormat {
+    /// The format of a file with a standard extension (`.gr`, `.mtx`, `.graphml` or `.csv`).
+    /// Returns None for other files, e.g. the `.gph` files which can be sparse or dense
+    pub fn from_extension(file_path: &str) -> Option<Self> {
+        let extension = Path::new(file_path).extension()?.to_str()?;
+        match extension.to_ascii_lowercase().as_str() {
+            "gr" => Some(GraphFormat::Dimacs),
+            "mtx" => Some(GraphFormat::MatrixMarket),
+            "graphml" => Some(GraphFormat::GraphML),
+            "csv" => Some(GraphFormat::Csv),
+            _ => None,
+        }
+    }
+}
+
+/// A graph loaded from a file, together with what the file says about it besides the edges
+#[derive(Clone, Debug)]
+pub struct GraphFile<G> {
+    pub graph: G,
+    /// The labels of the nodes in the order of the node indices, only for a labelled file
+    pub labels: Option<Labels>,
+    /// The number of edges in the file, an undirected edge counts once while the graph has it in
+    /// both directions
+    pub edge_count: usize,
+}
+
+impl<G> GraphFile<G> {
+    // A file without labels
+    pub(crate) fn unlabelled(graph: G, edge_count: usize) -> Self {
+        GraphFile {
+            graph,
+            labels: None,
+            edge_count,
+        }
+    }
+
+    /// Convert the graph and keep the rest
+    pub fn map<H>(self, convert: impl FnOnce(G) -> H) -> GraphFile<H> {
+        GraphFile {
+            graph: convert(self.graph),
+            labels: self.labels,
+            edge_count: self.edge_count,
+        }
+    }
+}
+
 pub trait FromGraphFile<W>
 where
     W: Num,
 {
     fn instantiate_graph(nodes: usize, edges: usize) -> Self;
     fn add_edge(graph: &mut Self, from: usize, to: usize, weight: W);
-    fn from_sparse_file(file_path: &str) -> Self;
-    fn from_dense_file(file_path: &str) -> Self;
+    /// Load a graph in the sparse format with the options from the header of the file
+    fn from_sparse_file(file_path: &str) -> Result<Self, LoadError>
+    where
+        Self: Sized,
+    {
+        Self::from_sparse_file_with(file_path, &LoadOptions::default())
+    }
+    fn from_sparse_file_with(file_path: &str, options: &LoadOptions) -> Result<Self, LoadError>
+    where
+        Self: Sized;
+    fn from_dense_file(file_path: &str) -> Result<Self, LoadError>
+    where
+        Self: Sized;
+    /// Load a graph in the sparse format with labels instead of node ids, together with the
+    /// labels of the nodes
+    fn from_labelled_file(
+        file_path: &str,
+        options: &LoadOptions,
+    ) -> Result<(Self, Labels), LoadError>
+    where
+        Self: Sized,
+        W: Weight,
+    {
+        let options = options.labelled(true);
+        let file = Self::from_graph_file(file_path, GraphFormat::Sparse, &options)?;
+        Ok((file.graph, file.labels.unwrap_or_default()))
+    }
+    /// Load a graph in any of the supported formats
+    fn from_file_with(
+        file_path: &str,
+        format: GraphFormat,
+        options: &LoadOptions,
+    ) -> Result<Self, LoadError>
+    where
+        Self: Sized,
+        W: Weight,
+    {
+        Self::from_graph_file(file_path, format, options).map(|file| file.graph)
+    }
+    /// Load a graph in any of the supported formats together with the labels of the nodes and
+    /// the number of edges in the file. GraphML files are always labelled, the sparse and CSV
+    /// files if the options or the header say so
+    fn from_graph_file(
+        file_path: &str,
+        format: GraphFormat,
+        options: &LoadOptions,
+    ) -> Result<GraphFile<Self>, LoadError>
+    where
+        Self: Sized,
+        W: Weight,
+    {
+        let (instantiate_graph, add_edge) = (Self::instantiate_graph, Self::add_edge);
+        match format {
+            GraphFormat::Sparse => load_sparse(file_path, options, instantiate_graph, add_edge),
+            GraphFormat::Dense => read_dense(file_path, instantiate_graph, add_edge),
+            GraphFormat::Dimacs => read_dimacs(file_path, options, instantiate_graph, add_edge),
+            GraphFormat::MatrixMarket => {
+                read_matrix_market(file_path, options, instantiate_graph, add_edge)
+            }
+            GraphFormat::GraphML => read_graphml(file_path, options, instantiate_graph, add_edge),
+            GraphFormat::Csv => read_csv(file_path, options, instantiate_graph, add_edge),
+        }
+    }
 }
 
-fn parse_number<T>(num: &str) -> T
+pub(crate) fn parse_number<T>(token: &str, file_path: &str, line: usize) -> Result<T, LoadError>
 where
     T: Num,
 {
     // num.parse::<T>()
-    match <T as Num>::from_str_radix(num, 10) {
-        Ok(number) => number,
-        Err(_) => panic!("Can't parse input"),
+    <T as Num>::from_str_radix(token, 10).map_err(|_| LoadError::InvalidNumber {
+        path: file_path.to_string(),
+        line,
+        token: token.to_string(),
+    })
+}
+
+// Parse a weight. Float types also accept "inf" and "NaN", which are not valid weights. For those
+// (and only those) values x * 0 is not zero, which also works for the integer types. The largest
+// integer is INFINITY, so it is rejected as well, like the values that are left for clamped sums
+pub(crate) fn parse_weight<W>(token: &str, file_path: &str, line: usize) -> Result<W, LoadError>
+where
+    W: Weight,
+{
+    let weight = parse_number::<W>(token, file_path, line)?;
+    if weight * W::zero() != W::zero() {
+        return Err(LoadError::InvalidNumber {
+            path: file_path.to_string(),
+            line,
+            token: token.to_string(),
+        });
+    }
+    if weight > W::MAX_FINITE {
+        return Err(LoadError::WeightTooLarge {
+            path: file_path.to_string(),
+            line,
+            token: token.to_string(),
+            max: format!("{:?}", W::MAX_FINITE),
+        });
+    }
+    if weight < W::MIN_FINITE {
+        return Err(LoadError::WeightTooSmall {
+            path: file_path.to_string(),
+            line,
+            token: token.to_string(),
+            min: format!("{:?}", W::MIN_FINITE),
+        });
+    }
+    Ok(weight)
+}
+
+// Parse a node id and check that it is in the graph. The index shift is applied here, such that
+// a node 0 in a file which starts counting at 1 is reported instead of underflowing
+pub(crate) fn parse_node(
+    token: &str,
+    offset: usize,
+    node_count: usize,
+    file_path: &str,
+    line: usize,
+) -> Result<usize, LoadError> {
+    let node = parse_number::<usize>(token, file_path, line)?;
+    match node.checked_sub(offset) {
+        Some(node) if node < node_count => Ok(node),
+        _ => Err(LoadError::NodeOutOfRange {
+            path: file_path.to_string(),
+            line,
+            token: token.to_string(),
+            node_count,
+        }),
     }
 }
 
-fn open_file(file_path: &str) -> File {
+pub(crate) fn open_file(file_path: &str) -> Result<File, LoadError> {
     let filepath = Path::new(file_path);
-    File::open(filepath).expect("Failed to open file")
+    File::open(filepath).map_err(|source| LoadError::Io {
+        path: file_path.to_string(),
+        source,
+    })
 }
 
-// Read the file from the command line
-pub fn load_sparse_graph<W, G>(
+// Read the next line of the file, together with its line number
+pub(crate) fn read_line(
+    lines: &mut Enumerate<Lines<BufReader<File>>>,
+    file_path: &str,
+) -> Option<Result<(usize, String), LoadError>> {
+    let (index, line) = lines.next()?;
+    Some(
+        line.map(|line| (index + 1, line))
+            .map_err(|source| LoadError::Io {
+                path: file_path.to_string(),
+                source,
+            }),
+    )
+}
+
+// The first line of a file, with the sizes of the graph followed by the flags
+struct Header {
+    line: usize,
+    content: String,
+    sizes: Vec<usize>,
+    flags: Vec<String>,
+}
+
+impl Header {
+    fn invalid(self, file_path: &str) -> LoadError {
+        LoadError::InvalidHeader {
+            path: file_path.to_string(),
+            line: self.line,
+            content: self.content,
+        }
+    }
+}
+
+// Read the header and check that it starts with the expected number of values, the words after
+// these are returned as flags
+fn read_header(
+    lines: &mut Enumerate<Lines<BufReader<File>>>,
     file_path: &str,
-    apply_index_shift: bool, // if true, substracts 1 from the node id in the file
+    expected: usize,
+) -> Result<Header, LoadError> {
+    let (line, content) = match read_line(lines, file_path) {
+        Some(line) => line?,
+        None => {
+            return Err(LoadError::MissingHeader {
+                path: file_path.to_string(),
+            })
+        }
+    };
+
+    let tokens: Vec<&str> = content.split_whitespace().collect();
+    let sizes = tokens
+        .iter()
+        .take(expected)
+        .map(|token| parse_number(token, file_path, line))
+        .collect::<Result<Vec<usize>, LoadError>>()?;
+    let flags = tokens.iter().skip(expected).map(|flag| flag.to_string()).collect();
+
+    let header = Header {
+        line,
+        content,
+        sizes,
+        flags,
+    };
+    if header.sizes.len() != expected {
+        return Err(header.invalid(file_path));
+    }
+
+    Ok(header)
+}
+
+// The format of a sparse file after combining the options with the header
+struct SparseFormat {
     directed: bool,
+    one_based: bool,
+    labelled: bool,
+}
+
+// Combine the options with the flags in the header of a sparse file. The options take
+// precedence over the header, unknown flags are an error
+fn resolve_options(
+    header: Header,
+    options: &LoadOptions,
+    file_path: &str,
+) -> Result<SparseFormat, LoadError> {
+    let mut from_header = LoadOptions::new();
+    for flag in &header.flags {
+        match flag.as_str() {
+            "directed" => from_header.directed = Some(true),
+            "undirected" => from_header.directed = Some(false),
+            "one-based" => from_header.one_based = Some(true),
+            "zero-based" => from_header.one_based = Some(false),
+            "labelled" => from_header.labelled = Some(true),
+            _ => return Err(header.invalid(file_path)),
+        }
+    }
+
+    Ok(SparseFormat {
+        directed: options.directed.or(from_header.directed).unwrap_or(false),
+        one_based: options.one_based.or(from_header.one_based).unwrap_or(true),
+        labelled: options.labelled.or(from_header.labelled).unwrap_or(false),
+    })
+}
+
+// Split a line of a labelled file, a label with spaces is written between double quotes
+fn split_labelled_line<'a>(
+    line: &'a str,
+    file_path: &str,
+    line_number: usize,
+) -> Result<Vec<&'a str>, LoadError> {
+    let mut tokens = Vec::new();
+    let mut rest = line.trim_start();
+    while !rest.is_empty() {
+        let (token, remaining) = match rest.strip_prefix('"') {
+            Some(quoted) => match quoted.find('"') {
+                Some(end) => (&quoted[..end], &quoted[end + 1..]),
+                None => {
+                    return Err(LoadError::UnterminatedQuote {
+                        path: file_path.to_string(),
+                        line: line_number,
+                    })
+                }
+            },
+            None => {
+                let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                (&rest[..end], &rest[end..])
+            }
+        };
+        tokens.push(token);
+        rest = remaining.trim_start();
+    }
+    Ok(tokens)
+}
+
+// Give the label the next free index, a labelled file can't have more labels than nodes
+pub(crate) fn intern_label(
+    labels: &mut Labels,
+    label: &str,
+    node_count: usize,
+    file_path: &str,
+    line: usize,
+) -> Result<usize, LoadError> {
+    if labels.index(label).is_none() && labels.len() >= node_count {
+        return Err(LoadError::NodeOutOfRange {
+            path: file_path.to_string(),
+            line,
+            token: label.to_string(),
+            node_count,
+        });
+    }
+    Ok(labels.intern(label))
+}
+
+/// Read a graph in the sparse format, see [`LoadOptions`] for the directedness and the node ids
+pub fn load_sparse_graph<W, G>(
+    file_path: &str,
+    options: &LoadOptions,
+    instantiate_graph: fn(nodes: usize, edges: usize) -> G,
+    add_edge: fn(graph: &mut G, from: usize, to: usize, weight: W),
+) -> Result<G, LoadError>
+where
+    W: Weight,
+{
+    load_sparse(file_path, options, instantiate_graph, add_edge).map(|file| file.graph)
+}
+
+// Read a sparse file, the labels are returned for a labelled file
+fn load_sparse<W, G>(
+    file_path: &str,
+    options: &LoadOptions,
     instantiate_graph: fn(nodes: usize, edges: usize) -> G,
     add_edge: fn(graph: &mut G, from: usize, to: usize, weight: W),
-) -> G
+) -> Result<GraphFile<G>, LoadError>
 where
-    W: Num + Copy,
+    W: Weight,
 {
-    let file = open_file(file_path);
-    let mut lines = BufReader::new(file).lines();
-
-    // Check the first line to get the graph size
-    let header = lines.next().expect("Can't find header of file").unwrap();
-    let size_line = header
-        .split_whitespace()
-        .map(parse_number)
-        .collect::<Vec<usize>>();
-
-    // let [node_count, edge_count] = &size_line[..];
-    let (node_count, edge_count) = match &size_line[..] {
-        [node_count, edge_count] => (*node_count, *edge_count),
-        _ => panic!("Failed to parse graph header."),
+    let file = open_file(file_path)?;
+    let mut lines = BufReader::new(file).lines().enumerate();
+
+    // Check the first line to get the graph size and the flags
+    let header = read_header(&mut lines, file_path, 2)?;
+    let (node_count, edge_count) = (header.sizes[0], header.sizes[1]);
+    let format = resolve_options(header, options, file_path)?;
+    let offset = if format.one_based { 1 } else { 0 };
+    let mut labels = if format.labelled {
+        Some(Labels::new())
+    } else {
+        None
     };
 
     let mut graph: G = instantiate_graph(node_count, edge_count);
+    let mut edges_read = 0;
 
     // Read all the other lines
-    for line in lines {
-        let line = line.unwrap();
-        let read_line: Vec<&str> = line.split_whitespace().collect();
+    while let Some(line) = read_line(&mut lines, file_path) {
+        let (line_number, line) = line?;
+        let read_line: Vec<&str> = match labels {
+            Some(_) => split_labelled_line(&line, file_path, line_number)?,
+            None => line.split_whitespace().collect(),
+        };
+
+        // Skip empty lines, e.g. at the end of the file
+        if read_line.is_empty() {
+            continue;
+        }
+        if read_line.len() < 3 {
+            return Err(LoadError::MissingValue {
+                path: file_path.to_string(),
+                line: line_number,
+                expected: 3,
+                found: read_line.len(),
+            });
+        }
 
         // Write the values in the line to a store
-        let offset = if apply_index_shift { 1 } else { 0 };
-        let node_a = parse_number::<usize>(read_line[0]) - offset;
-        let node_b = parse_number::<usize>(read_line[1]) - offset;
-
-        let weight = parse_number::<W>(read_line[2]);
+        let (node_a, node_b) = match &mut labels {
+            Some(labels) => (
+                intern_label(labels, read_line[0], node_count, file_path, line_number)?,
+                intern_label(labels, read_line[1], node_count, file_path, line_number)?,
+            ),
+            None => (
+                parse_node(read_line[0], offset, node_count, file_path, line_number)?,
+                parse_node(read_line[1], offset, node_count, file_path, line_number)?,
+            ),
+        };
+
+        let weight = parse_weight::<W>(read_line[2], file_path, line_number)?;
 
         add_edge(&mut graph, node_a, node_b, weight);
 
-        if !directed {
+        if !format.directed {
             add_edge(&mut graph, node_b, node_a, weight);
         }
+        edges_read += 1;
     }
 
-    graph
+    Ok(GraphFile {
+        graph,
+        labels,
+        edge_count: edges_read,
+    })
 }
 
 fn load_dense_graph<W, G>(
     file_path: &str,
-    apply_index_shift: bool,
     instantiate_graph: fn(nodes: usize, edges: usize) -> G,
     add_edge: fn(graph: &mut G, from: usize, to: usize, weight: W),
-) -> G
+) -> Result<G, LoadError>
 where
-    W: Num + Copy,
+    W: Weight,
 {
-    let file = open_file(file_path);
-    let mut lines = BufReader::new(file).lines();
-
-    let header = lines.next().expect("Can't find header of file").unwrap();
-    let size_line = header
-        .split_whitespace()
-        .map(parse_number)
-        .collect::<Vec<usize>>();
-
-    let size = match &size_line[..] {
-        [size] => size,
-        _ => panic!("Failed to parse graph header."),
-    };
+    read_dense(file_path, instantiate_graph, add_edge).map(|file| file.graph)
+}
+
+// The rows and columns of the dense format are positions in the matrix, so there is no index
+// shift. Every entry of the matrix is an edge
+fn read_dense<W, G>(
+    file_path: &str,
+    instantiate_graph: fn(nodes: usize, edges: usize) -> G,
+    add_edge: fn(graph: &mut G, from: usize, to: usize, weight: W),
+) -> Result<GraphFile<G>, LoadError>
+where
+    W: Weight,
+{
+    let file = open_file(file_path)?;
+    let mut lines = BufReader::new(file).lines().enumerate();
 
-    let mut graph = instantiate_graph(*size, size * size);
+    // The matrix says for every pair if there is an edge, so there are no flags
+    let header = read_header(&mut lines, file_path, 1)?;
+    if !header.flags.is_empty() {
+        return Err(header.invalid(file_path));
+    }
+    let size = header.sizes[0];
+
+    let mut graph = instantiate_graph(size, size * size);
 
     // Read the remaining lines to populate the adjacency matrix
-    for (from, line) in lines.enumerate() {
+    let mut from = 0;
+    while let Some(line) = read_line(&mut lines, file_path) {
+        let (line_number, line) = line?;
+
+        // Skip empty lines, e.g. at the end of the file
+        if line.trim().is_empty() {
+            continue;
+        }
+
         let row: Vec<W> = line
-            .expect("Failed to read line")
             .split_whitespace()
-            .map(parse_number)
-            .collect();
+            .map(|token| parse_weight(token, file_path, line_number))
+            .collect::<Result<Vec<W>, LoadError>>()?;
 
         // Check if the number of columns matches the expected number of stores
-        if row.len() != *size {
-            panic!("Unexpected number of columns in line {}", from + 1);
+        if row.len() != size {
+            return Err(LoadError::ColumnCount {
+                path: file_path.to_string(),
+                line: line_number,
+                expected: size,
+                found: row.len(),
+            });
+        }
+
+        // There can't be more rows than nodes
+        if from >= size {
+            return Err(LoadError::RowCount {
+                path: file_path.to_string(),
+                expected: size,
+                found: from + 1,
+            });
         }
 
         for (to, edge) in row.iter().enumerate() {
-            if apply_index_shift {
-                add_edge(&mut graph, from - 1, to - 1, *edge);
-            } else {
-                add_edge(&mut graph, from, to, *edge);
-            }
+            add_edge(&mut graph, from, to, *edge);
         }
+        from += 1;
+    }
+
+    if from != size {
+        return Err(LoadError::RowCount {
+            path: file_path.to_string(),
+            expected: size,
+            found: from,
+        });
     }
 
-    graph
+    Ok(GraphFile::unlabelled(graph, size * size))
 }
 
-impl<W: Num + Copy> FromGraphFile<W> for GraphAM<W> {
+impl<W: Weight> FromGraphFile<W> for GraphAM<W> {
     fn instantiate_graph(nodes: usize, _: usize) -> Self {
         GraphAM::with_capacity(nodes)
     }
@@ -146,99 +725,217 @@ impl<W: Num + Copy> FromGraphFile<W> for GraphAM<W> {
         graph.add_edge(from, to, weight);
     }
 
-    fn from_sparse_file(file_path: &str) -> Self {
+    fn from_sparse_file_with(file_path: &str, options: &LoadOptions) -> Result<Self, LoadError> {
+        load_sparse_graph(
+            file_path,
+            options,
+            Self::instantiate_graph,
+            FromGraphFile::add_edge,
+        )
+    }
+
+    fn from_dense_file(file_path: &str) -> Result<Self, LoadError> {
+        load_dense_graph(file_path, Self::instantiate_graph, Self::add_edge)
+    }
+}
+
+impl<W: Weight> FromGraphFile<W> for EdgeList<W> {
+    fn instantiate_graph(nodes: usize, edges: usize) -> Self {
+        EdgeList::with_capacity(nodes, edges)
+    }
+
+    fn add_edge(graph: &mut Self, from: usize, to: usize, weight: W) {
+        graph.add_edge(from, to, weight);
+    }
+
+    fn from_sparse_file_with(file_path: &str, options: &LoadOptions) -> Result<Self, LoadError> {
         load_sparse_graph(
             file_path,
-            true,
-            false,
+            options,
             Self::instantiate_graph,
             FromGraphFile::add_edge,
         )
     }
 
-    fn from_dense_file(file_path: &str) -> Self {
-        load_dense_graph(file_path, true, Self::instantiate_graph, Self::add_edge)
+    fn from_dense_file(file_path: &str) -> Result<Self, LoadError> {
+        load_dense_graph(file_path, Self::instantiate_graph, Self::add_edge)
     }
 }
 
-// impl<W: Num + Copy> FromGraphFile<W> for GraphAL<W> {
-//     fn instantiate_graph(nodes: usize, _: usize) -> Self {
-//         GraphAL::with_capacity(nodes)
-//     }
+// Inserting edges one by one in a CSR graph is slow, so the files are read into an edge list
+// which is converted in one go
+impl<W: Weight> FromGraphFile<W> for GraphCSR<W> {
+    fn instantiate_graph(nodes: usize, edges: usize) -> Self {
+        GraphCSR::with_capacity(nodes, edges)
+    }
+
+    fn add_edge(graph: &mut Self, from: usize, to: usize, weight: W) {
+        graph.add_edge(from, to, weight);
+    }
 
-//     fn add_edge(graph: &mut Self, from: usize, to: usize, weight: W) {
-//         graph.add_edge(from, to, weight);
-//     }
+    fn from_sparse_file_with(file_path: &str, options: &LoadOptions) -> Result<Self, LoadError> {
+        EdgeList::from_sparse_file_with(file_path, options).map(GraphCSR::from)
+    }
 
-//     fn from_sparse_file(file_path: &str) -> Self {
-//         load_sparse_graph(
-//             file_path,
-//             true,
-//             false,
-//             Self::instantiate_graph,
-//             FromGraphFile::add_edge,
-//         )
-//     }
+    fn from_dense_file(file_path: &str) -> Result<Self, LoadError> {
+        EdgeList::from_dense_file(file_path).map(GraphCSR::from)
+    }
 
-//     fn from_dense_file(file_path: &str) -> Self {
-//         load_dense_graph(file_path, true, Self::instantiate_graph, Self::add_edge)
-//     }
-// }
+    fn from_graph_file(
+        file_path: &str,
+        format: GraphFormat,
+        options: &LoadOptions,
+    ) -> Result<GraphFile<Self>, LoadError> {
+        EdgeList::from_graph_file(file_path, format, options).map(|file| file.map(GraphCSR::from))
+    }
+}
 
-impl<W: Num + Copy> FromGraphFile<W> for MatrixGraph<usize, W> {
+// All nodes of the header are added up front, such that the nodes without edges are in the graph
+// as well and the node with id i gets index i
+impl<W: Weight> FromGraphFile<W> for IdMatrixGraph<W> {
     fn instantiate_graph(nodes: usize, _: usize) -> Self {
-        MatrixGraph::with_capacity(nodes)
+        let mut graph = IdMatrixGraph::with_capacity(nodes);
+        for id in 0..nodes {
+            graph.add_node(id);
+        }
+        graph
     }
 
     fn add_edge(graph: &mut Self, from: usize, to: usize, weight: W) {
         graph.add_edge_by_ids(from, to, weight);
     }
 
-    fn from_sparse_file(file_path: &str) -> Self {
+    fn from_sparse_file_with(file_path: &str, options: &LoadOptions) -> Result<Self, LoadError> {
         load_sparse_graph(
             file_path,
-            true,
-            false,
+            options,
             FromGraphFile::instantiate_graph,
             FromGraphFile::add_edge,
         )
     }
 
-    fn from_dense_file(file_path: &str) -> Self {
+    fn from_dense_file(file_path: &str) -> Result<Self, LoadError> {
         load_dense_graph(
             file_path,
-            true,
             FromGraphFile::instantiate_graph,
             FromGraphFile::add_edge,
         )
     }
 }
 
-impl<W: Num + Copy> FromGraphFile<W> for Graph<usize, W> {
+impl<W: Weight> FromGraphFile<W> for IdGraph<W> {
     fn instantiate_graph(nodes: usize, edges: usize) -> Self {
-        Graph::with_capacity(nodes, edges)
+        let mut graph = IdGraph::with_capacity(nodes, edges);
+        for id in 0..nodes {
+            graph.add_node(id);
+        }
+        graph
     }
 
     fn add_edge(graph: &mut Self, from: usize, to: usize, weight: W) {
         graph.add_edge_by_ids(from, to, weight);
     }
 
-    fn from_sparse_file(file_path: &str) -> Self {
+    fn from_sparse_file_with(file_path: &str, options: &LoadOptions) -> Result<Self, LoadError> {
         load_sparse_graph(
             file_path,
-            true,
-            false,
+            options,
             FromGraphFile::instantiate_graph,
             FromGraphFile::add_edge,
         )
     }
 
-    fn from_dense_file(file_path: &str) -> Self {
+    fn from_dense_file(file_path: &str) -> Result<Self, LoadError> {
         load_dense_graph(
             file_path,
-            true,
             FromGraphFile::instantiate_graph,
             FromGraphFile::add_edge,
         )
     }
 }
+
+// The plain petgraph graphs are loaded with the id maps, such that loading stays linear
+impl<W: Weight> FromGraphFile<W> for MatrixGraph<usize, W> {
+    fn instantiate_graph(nodes: usize, _: usize) -> Self {
+        MatrixGraph::with_capacity(nodes)
+    }
+
+    fn add_edge(graph: &mut Self, from: usize, to: usize, weight: W) {
+        graph.add_edge_by_ids(from, to, weight);
+    }
+
+    fn from_sparse_file_with(file_path: &str, options: &LoadOptions) -> Result<Self, LoadError> {
+        IdMatrixGraph::from_sparse_file_with(file_path, options).map(NodeIdGraph::into_graph)
+    }
+
+    fn from_dense_file(file_path: &str) -> Result<Self, LoadError> {
+        IdMatrixGraph::from_dense_file(file_path).map(NodeIdGraph::into_graph)
+    }
+
+    fn from_graph_file(
+        file_path: &str,
+        format: GraphFormat,
+        options: &LoadOptions,
+    ) -> Result<GraphFile<Self>, LoadError> {
+        let file = IdMatrixGraph::from_graph_file(file_path, format, options)?;
+        Ok(file.map(NodeIdGraph::into_graph))
+    }
+}
+
+impl<W: Weight> FromGraphFile<W> for Graph<usize, W> {
+    fn instantiate_graph(nodes: usize, edges: usize) -> Self {
+        Graph::with_capacity(nodes, edges)
+    }
+
+    fn add_edge(graph: &mut Self, from: usize, to: usize, weight: W) {
+        graph.add_edge_by_ids(from, to, weight);
+    }
+
+    fn from_sparse_file_with(file_path: &str, options: &LoadOptions) -> Result<Self, LoadError> {
+        IdGraph::from_sparse_file_with(file_path, options).map(NodeIdGraph::into_graph)
+    }
+
+    fn from_dense_file(file_path: &str) -> Result<Self, LoadError> {
+        IdGraph::from_dense_file(file_path).map(NodeIdGraph::into_graph)
+    }
+
+    fn from_graph_file(
+        file_path: &str,
+        format: GraphFormat,
+        options: &LoadOptions,
+    ) -> Result<GraphFile<Self>, LoadError> {
+        let file = IdGraph::from_graph_file(file_path, format, options)?;
+        Ok(file.map(NodeIdGraph::into_graph))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn largest_integer_weight_is_rejected() {
+        assert_eq!(parse_weight::<u16>("65533", "g.gph", 2).unwrap(), 65533);
+        // 65534 is left for the clamped sums and 65535 is INFINITY
+        for token in ["65534", "65535"] {
+            assert!(matches!(
+                parse_weight::<u16>(token, "g.gph", 2),
+                Err(LoadError::WeightTooLarge { line: 2, .. })
+            ));
+        }
+        assert_eq!(parse_weight::<i8>("-127", "g.gph", 2).unwrap(), -127);
+        assert!(matches!(
+            parse_weight::<i8>("-128", "g.gph", 2),
+            Err(LoadError::WeightTooSmall { line: 2, .. })
+        ));
+        assert!(matches!(
+            parse_weight::<i32>("2147483647", "g.gph", 3),
+            Err(LoadError::WeightTooLarge { line: 3, .. })
+        ));
+        assert!(parse_weight::<f64>("1e308", "g.gph", 2).is_ok());
+        assert!(matches!(
+            parse_weight::<f64>("inf", "g.gph", 2),
+            Err(LoadError::InvalidNumber { .. })
+        ));
+    }
+}
@@ -2,197 +2,431 @@
 // 30-01-2024
 
 // Modules
-mod algorithms;
 mod analysis;
-mod base;
-mod fwb_function;
-mod graph;
-mod loader;
+mod cli;
+mod record;
 
 // Import crates
-use num::Num;
-use std::env;
-use std::fmt::Debug;
-use std::fs::OpenOptions;
-use std::io::Write;
-use std::time::Instant;
-
-// Import crates from different files
-use crate::algorithms::dijkstra_am::Dijkstra;
-use crate::algorithms::dijkstra_par::DijkstraPar;
-use crate::algorithms::example::BaseLineFloydWarshall;
-use crate::algorithms::floyd_warshall_am::FloydWarshall;
-use crate::algorithms::floyd_warshall_block::FloydWarshallBlock;
-use crate::algorithms::floyd_warshall_block_par::FloydWarshallBlockPar;
-use crate::analysis::{compute_average, compute_std};
-use crate::base::{APSPAlgorithm, APSPResult};
+use clap::Parser;
+use petgraph::algo::BoundedMeasure;
+use num::NumCast;
+use std::fmt::Display;
+use std::path::Path;
+use std::process;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+// Import crates from the library and different files
+use apsp::algorithms::dijkstra_am::Dijkstra;
+use apsp::algorithms::dijkstra_csr::DijkstraCSR;
+use apsp::algorithms::dijkstra_par::DijkstraPar;
+use apsp::algorithms::example::BaseLineFloydWarshall;
+use apsp::algorithms::floyd_warshall_am::FloydWarshall;
+use apsp::algorithms::floyd_warshall_block::FloydWarshallBlock;
+use apsp::algorithms::floyd_warshall_block_par::FloydWarshallBlockPar;
+use apsp::algorithms::johnson::Johnson;
+#[cfg(feature = "rayon")]
+use apsp::algorithms::{
+    dijkstra_rayon::DijkstraRayon, floyd_warshall_block_rayon::FloydWarshallBlockRayon,
+    floyd_warshall_rayon::FloydWarshallRayon,
+};
+#[cfg(feature = "rayon")]
+use apsp::Pool;
+use apsp::{
+    APSPAlgorithm, APSPResult, ExecuteError, FromGraphFile, GraphCSR, GraphFormat, GraphGenerator,
+    Labels, Weight,
+};
+use apsp::export::{write_graph, ExportOptions};
+use crate::analysis::{compute_average, compute_confidence_interval, compute_std, summarize};
+use crate::cli::{
+    Algorithm, BenchOptions, Cli, Command, EvalOptions, Format, ModelArgs, RunArgs, WeightKind,
+};
+use crate::record::{append_record, output_format, BenchRecord};
+
+// Loading or writing a graph can fail on a malformed or missing file, there is nothing to run then
+// so report and stop
+fn exit_on_error<E: Display>(result: Result<(), E>) {
+    if let Err(error) = result {
+        eprintln!("Error: {}", error);
+        process::exit(1);
+    }
+}
+
+// Load the graph of the command line into an algorithm, returns the labels of a labelled file
+fn load<W: Weight>(args: &RunArgs, algorithm: &mut dyn APSPAlgorithm<W>) -> Option<Labels> {
+    let labels = algorithm.load_graph_from(&args.file, args.graph_format(), &args.load_options());
+    labels.unwrap_or_else(|error| {
+        eprintln!("Error: {}", error);
+        process::exit(1);
+    })
+}
 
 // Function to measure the algorithm and which returns the result together with the execution time
-fn measure_algo<W: Num + Copy + Debug>(
-    file_path: &str,
+// and the labels of the nodes
+fn measure_algo<W: Weight>(
+    args: &RunArgs,
     algorithm: &mut dyn APSPAlgorithm<W>,
-) -> (u128, APSPResult<W>) {
-    algorithm.load_graph(file_path, true);
+) -> Result<(f64, APSPResult<W>, Option<Labels>), ExecuteError> {
+    let labels = load(args, algorithm);
 
-    let exec_start = Instant::now();
-    algorithm.execute();
-    let exec_time = exec_start.elapsed();
+    let exec_time = time_execution(algorithm)?;
 
     // check for correctness
     let result = algorithm.get_result();
 
-    (exec_time.as_millis(), result)
+    Ok((exec_time, result, labels))
 }
 
-// Function to measure the stats of an algorithm
-// There is also a possibility to write the output to a file
-fn measure_algo_stats<W: Num + Copy + Debug>(
-    file_path: &str,
+// Function to time one execution in milliseconds, with the nanosecond resolution of Instant
+fn time_execution<W: Weight>(algorithm: &mut dyn APSPAlgorithm<W>) -> Result<f64, ExecuteError> {
+    let exec_start = Instant::now();
+    let execution = algorithm.execute();
+    let exec_time = exec_start.elapsed();
+    execution.map(|_| exec_time.as_nanos() as f64 / 1e6)
+}
+
+// Function to measure the execution times of an algorithm in milliseconds
+// After the warm-up it runs the given number of iterations, and with a precision it continues
+// until the confidence interval of the mean is narrow enough or the maximum is reached
+fn measure_algo_stats<W: Weight>(
+    args: &RunArgs,
     algorithm: &mut dyn APSPAlgorithm<W>,
-    num_iter: usize,
-    write: bool,
-    write_to: &str,
-) -> (f64, f64) {
+    bench: &BenchOptions,
+) -> Result<Vec<f64>, ExecuteError> {
     // Load the graph
-    algorithm.load_graph(file_path, true);
+    load(args, algorithm);
+
+    // The first executions are slower, because the caches are cold and memory is allocated
+    for _ in 0..bench.warmup {
+        time_execution(algorithm)?;
+    }
 
     // Define an empty vector for the time
     let mut times = vec![];
-
-    // Loop num_iter amount the same time
-    for _ in 0..num_iter {
-        // Start measuring time execute file and return the execution time which is pushed to times vector
-        let exec_start = Instant::now();
-        algorithm.execute();
-        let exec_time = exec_start.elapsed();
-        times.push(exec_time.as_millis() as f64);
+    let max_iterations = match bench.precision {
+        Some(_) => bench.max_iterations.max(bench.iterations),
+        None => bench.iterations,
+    };
+    while times.len() < max_iterations {
+        times.push(time_execution(algorithm)?);
+        if times.len() < bench.iterations {
+            continue;
+        }
+        match bench.precision {
+            Some(precision) if times.len() >= 2 => {
+                let average = compute_average(&times);
+                let std = compute_std(&times, average);
+                let (low, high) = compute_confidence_interval(&times, average, std);
+                if (high - low) / 2.0 <= precision * average {
+                    break;
+                }
+            }
+            Some(_) => {}
+            None => break,
+        }
     }
+    Ok(times)
+}
 
-    // Compute the average and the std
-    let average = compute_average(&times);
-    let std = compute_std(&times, average);
-
-    // If you want to write, write the results to a file
-    if write {
-        if let Ok(mut file) = OpenOptions::new()
-            .create(true)
-            .write(true)
-            .append(true)
-            .open(write_to)
-        {
-            if let Err(e) = writeln!(file, "{:.05} {:.05}", average, std) {
-                eprintln!("Error writing to file: {}", e);
-            }
+// Box an algorithm, for the evaluation with the paths and the overflow check
+macro_rules! configured {
+    ($instance:expr, $check:expr) => {
+        if $check {
+            Box::new($instance.with_paths().with_overflow_check()) as Box<dyn APSPAlgorithm<W>>
         } else {
-            eprintln!("Error opening file");
+            Box::new($instance) as Box<dyn APSPAlgorithm<W>>
         }
-    }
-    // return the average and standard deviation
-    (average, std)
+    };
 }
 
-fn print_type_of<T>(_: &T) -> String {
-    std::any::type_name::<T>().to_string()
+// Creates the algorithms with the parameters of the command line
+struct Algorithms<'a> {
+    args: &'a RunArgs,
+    // The rayon variants all run on the same pool
+    #[cfg(feature = "rayon")]
+    pool: Pool,
 }
 
-macro_rules! measure_all {    // Base case: when there are no more structs to instantiate
-    () => {};
-    ($instance_path:expr, $num_iter:expr, $write: expr, $write_to:expr, []) => {};
-    ($instance_path:expr, $num_iter:expr, $write: expr, $write_to:expr, [$instance: expr$(, $($rest:tt)*)?]) => {
-        let mut instance = $instance;
-        let algo_id = print_type_of(&instance);
-        let algo_id = algo_id.split("::").last().unwrap();
-
-        let (avg_fw, std_fw) = measure_algo_stats(
-            $instance_path,
-            &mut instance,
-            $num_iter,
-            $write,
-            $write_to,
-        );
-        println!("{: <30} {:.03} +/- {:.03} ms", algo_id, avg_fw, std_fw);
+impl<'a> Algorithms<'a> {
+    fn new(args: &'a RunArgs) -> Self {
+        Algorithms {
+            args,
+            #[cfg(feature = "rayon")]
+            pool: Pool::new(args.threads).unwrap_or_else(|error| {
+                eprintln!("Error: can't start the thread pool: {}", error);
+                process::exit(1);
+            }),
+        }
+    }
 
-        measure_all!($instance_path, $num_iter, $write, $write_to, [$($($rest)*)?]);
+    // Create the algorithm, `check` also tracks the paths and checks for overflow
+    fn build<W>(&self, algorithm: Algorithm, check: bool) -> Box<dyn APSPAlgorithm<W>>
+    where
+        W: Weight + BoundedMeasure + Send + Sync + 'static,
+    {
+        let args = self.args;
+        match algorithm {
+            Algorithm::Petgraph => Box::new(BaseLineFloydWarshall::<W>::new()),
+            Algorithm::FloydWarshall => configured!(FloydWarshall::<W>::new(), check),
+            Algorithm::Dijkstra => configured!(Dijkstra::<W>::new(), check),
+            Algorithm::DijkstraPar => configured!(DijkstraPar::<W>::new(args.threads), check),
+            Algorithm::FloydWarshallBlock => {
+                configured!(FloydWarshallBlock::<W>::new(args.blocks), check)
+            }
+            Algorithm::FloydWarshallBlockPar => configured!(
+                FloydWarshallBlockPar::<W>::new(args.threads, args.block_size),
+                check
+            ),
+            Algorithm::Johnson => configured!(Johnson::<W>::new(), check),
+            Algorithm::DijkstraCsr => configured!(DijkstraCSR::<W>::new(), check),
+            #[cfg(feature = "rayon")]
+            Algorithm::DijkstraRayon => configured!(DijkstraRayon::<W>::new(&self.pool), check),
+            #[cfg(feature = "rayon")]
+            Algorithm::FloydWarshallRayon => {
+                configured!(FloydWarshallRayon::<W>::new(&self.pool), check)
+            }
+            #[cfg(feature = "rayon")]
+            Algorithm::FloydWarshallBlockRayon => configured!(
+                FloydWarshallBlockRayon::<W>::new(&self.pool, args.block_size),
+                check
+            ),
+        }
+    }
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let (args, eval, bench) = match cli.command {
+        Command::Eval(command) => (command.run, Some(command.eval), None),
+        Command::Bench(command) => (command.run, None, Some(command.bench)),
+        Command::All(command) => (command.run, Some(command.eval), Some(command.bench)),
+        Command::Generate(command) => return generate(&command.model),
     };
+
+    // Print the instance path
+    println!("{}", args.file);
+
+    // The float distances are summed in a different order by every algorithm, so they are
+    // compared with a small tolerance by default
+    let (eval, bench) = (eval.as_ref(), bench.as_ref());
+    match args.weight {
+        WeightKind::U16 => run::<u16>(&args, eval, bench, "0"),
+        WeightKind::U32 => run::<u32>(&args, eval, bench, "0"),
+        WeightKind::I32 => run::<i32>(&args, eval, bench, "0"),
+        WeightKind::F32 => run::<f32>(&args, eval, bench, "1e-3"),
+        WeightKind::F64 => run::<f64>(&args, eval, bench, "1e-9"),
+    }
 }
 
-macro_rules! eval_all {    // Base case: when there are no more structs to instantiate
-    () => {};
-    ($instance_path:expr, $result:expr, []) => {};
-    ($instance_path:expr, $result:expr, [$instance: expr$(, $($rest:tt)*)?]) => {
-        let mut instance = $instance;
-        let algo_id = print_type_of(&instance);
-        let algo_id = algo_id.split("::").last().unwrap();
-        let (duration_fw, fw_result) = measure_algo($instance_path, &mut instance);
-        let evaluation: bool = fw_result.result_compare($result);
-        println!("{: <30} Runtime: {}ms", algo_id, duration_fw);
-        println!("{: <30} Evaluation: {}", algo_id, evaluation);
-        println!("{:-<30}", "");
+// Generate a random graph with the model of the command line and write it to the output file
+fn generate(model: &ModelArgs) {
+    let (model, options) = model.split();
+    let generator = GraphGenerator::new(model)
+        .directed(options.directed)
+        .with_weights(options.weights)
+        .with_seed(options.seed);
 
-        eval_all!($instance_path, $result, [$($($rest)*)?]);
+    let format = Format::graph_format(options.format, &options.output);
+    let edges = match options.weight {
+        WeightKind::U16 => write_generated::<u16>(&generator, &options.output, format),
+        WeightKind::U32 => write_generated::<u32>(&generator, &options.output, format),
+        WeightKind::I32 => write_generated::<i32>(&generator, &options.output, format),
+        WeightKind::F32 => write_generated::<f32>(&generator, &options.output, format),
+        WeightKind::F64 => write_generated::<f64>(&generator, &options.output, format),
     };
+    println!(
+        "Generated: {} ({} nodes, {} edges)",
+        options.output,
+        generator.model.node_count(),
+        edges
+    );
 }
 
-fn main() {
-    // Collect arguments from the command line
-    let args: Vec<String> = env::args().collect();
-
-    // Define the root path
-    let root_path = env!("CARGO_MANIFEST_DIR");
-    let mut instance_path = format!("{root_path}/instances/b18.gph");
-    let mut write = false;
-    let mut write_to = root_path.to_string();
-
-    // Read arguments from the command line
-    if args.len() == 2 {
-        let filename: String = args[1].parse().expect("This filename does not work");
-        instance_path = format!("{root_path}/{filename}");
-    } else if args.len() == 3 {
-        let filename: String = args[1].parse().expect("This filename does not work");
-        instance_path = format!("{root_path}/{filename}");
-        write = true;
-        let filename: String = args[2].parse().expect("This filename does not work");
-        write_to = format!("{root_path}/results/{filename}");
+// Write the generated graph with the weight type W, returns the number of edges in the file
+fn write_generated<W>(generator: &GraphGenerator, file_path: &str, format: GraphFormat) -> usize
+where
+    W: Weight + NumCast + Display,
+{
+    let edge_list = generator.generate::<W>().unwrap_or_else(|error| {
+        eprintln!("Error: {}", error);
+        process::exit(1);
+    });
+    let options = ExportOptions::new().undirected(!generator.directed);
+    exit_on_error(write_graph(file_path, format, &edge_list, &options));
+    if generator.directed {
+        edge_list.edges.len()
+    } else {
+        edge_list.edges.len() / 2
     }
+}
 
-    // Print the instance path
-    println!("{}", instance_path);
+// Evaluate and/or measure the selected algorithms with the weight type W. An algorithm which fails,
+// e.g. on a negative cycle, is reported and the others still run, the exit code is 1 then
+fn run<W>(
+    args: &RunArgs,
+    eval: Option<&EvalOptions>,
+    bench: Option<&BenchOptions>,
+    default_tolerance: &str,
+) where
+    W: Weight + BoundedMeasure + Send + Sync + 'static,
+{
+    let algorithms = Algorithms::new(args);
+    let mut failures = 0;
+    if let Some(eval) = eval {
+        let tolerance = eval.tolerance.as_deref().unwrap_or(default_tolerance);
+        failures += evaluate::<W>(&algorithms, tolerance);
+    }
+    if let Some(bench) = bench {
+        failures += benchmark::<W>(&algorithms, bench);
+    }
+    if failures > 0 {
+        process::exit(1);
+    }
+}
+
+// Compare the results of the selected algorithms with the Floyd-Warshall of petgraph, returns the
+// number of algorithms which failed
+fn evaluate<W>(algorithms: &Algorithms, tolerance: &str) -> usize
+where
+    W: Weight + BoundedMeasure + Send + Sync + 'static,
+{
+    let args = algorithms.args;
+    let tolerance = match W::from_str_radix(tolerance, 10) {
+        Ok(tolerance) => tolerance,
+        Err(_) => {
+            eprintln!("Error: can't parse the tolerance \"{}\" as a weight", tolerance);
+            process::exit(1);
+        }
+    };
 
     // compute the results for all our different algorithms and compare the results with the PetGraph package
     // If correct print true, otherwise false
-    let mut base_floyd_warshall: BaseLineFloydWarshall<u16> = BaseLineFloydWarshall::new();
-    let (duration_bfw, bfw_result) = measure_algo(&instance_path, &mut base_floyd_warshall);
-    println!("FW petgraph:                   Runtime: {}ms", duration_bfw);
-
-    const PARALLEL_FW_THREADS: usize = 10;
-    const PARALLEL_FW_BLOCK_SIZE: usize = 10;
-    eval_all!(
-        &instance_path,
-        &bfw_result.shortest_paths,
-        [
-            FloydWarshall::<u16>::new(),
-            Dijkstra::<u16>::new(),
-            DijkstraPar::<u16>::new(PARALLEL_FW_THREADS),
-            FloydWarshallBlock::<u16>::new(PARALLEL_FW_BLOCK_SIZE),
-            FloydWarshallBlockPar::<u16>::new(PARALLEL_FW_THREADS)
-        ]
-    );
+    let mut base_floyd_warshall: BaseLineFloydWarshall<W> = BaseLineFloydWarshall::new();
+    // Without the reference there is nothing to compare with
+    let reference = measure_algo(args, &mut base_floyd_warshall);
+    let (duration_bfw, bfw_result, labels) = reference.unwrap_or_else(|error| {
+        eprintln!("Error: {}", error);
+        process::exit(1);
+    });
+    println!("FW petgraph:                   Runtime: {:.03}ms", duration_bfw);
 
-    let num_iter = 10;
-    let threads = 10;
-    let num_blocks = 10;
-    measure_all!(
-        &instance_path,
-        num_iter,
-        write,
-        &write_to,
-        [
-            BaseLineFloydWarshall::<u16>::new(),
-            FloydWarshall::<u16>::new(),
-            Dijkstra::<u16>::new(),
-            DijkstraPar::<u16>::new(threads),
-            FloydWarshallBlock::<u16>::new(num_blocks),
-            FloydWarshallBlockPar::<u16>::new(threads)
-        ]
-    );
+    // Print the path between the first and the last node to check the path reconstruction, by
+    // label for a labelled file
+    let path_from = 0;
+    let path_to = bfw_result.node_count().saturating_sub(1);
+    let mut failures = 0;
+    for algorithm in args.selected_algorithms() {
+        if algorithm == Algorithm::Petgraph {
+            continue;
+        }
+        let algo_id = algorithm.name();
+        let mut instance = algorithms.build::<W>(algorithm, true);
+        let (duration_fw, fw_result) = match measure_algo(args, instance.as_mut()) {
+            Ok((duration, result, _)) => (duration, result),
+            Err(error) => {
+                println!("{: <30} Evaluation: failed, {}", algo_id, error);
+                println!("{:-<30}", "");
+                failures += 1;
+                continue;
+            }
+        };
+        let report = fw_result.compare(&bfw_result, tolerance);
+        println!("{: <30} Runtime: {:.03}ms", algo_id, duration_fw);
+        println!("{: <30} Evaluation: {}", algo_id, report.is_equal());
+        if !report.is_equal() {
+            println!("{}", report);
+        }
+        match (fw_result.path(path_from, path_to), &labels) {
+            (Some(path), Some(labels)) => {
+                let path = labels.path_labels(&path);
+                let (from, to) = (&path[0], &path[path.len() - 1]);
+                println!("{: <30} Path {} -> {}: {:?}", algo_id, from, to, path);
+            }
+            (Some(path), None) => {
+                println!("{: <30} Path {} -> {}: {:?}", algo_id, path_from, path_to, path);
+            }
+            (None, _) => {}
+        }
+        println!("{:-<30}", "");
+    }
+    failures
+}
+
+// Measure the runtime of the selected algorithms, and write a record for each of them if an
+// output file is given. Returns the number of algorithms which failed, they get no record
+fn benchmark<W>(algorithms: &Algorithms, bench: &BenchOptions) -> usize
+where
+    W: Weight + BoundedMeasure + Send + Sync + 'static,
+{
+    let args = algorithms.args;
+
+    // Load the graph once more for the node and edge counts of the records, an undirected edge
+    // counts once as in the file
+    let format = args.graph_format();
+    let file = GraphCSR::<W>::from_graph_file(&args.file, format, &args.load_options());
+    let file = file.unwrap_or_else(|error| {
+        eprintln!("Error: {}", error);
+        process::exit(1);
+    });
+    let graph_name = Path::new(&args.file)
+        .file_stem()
+        .map_or(args.file.clone(), |stem| stem.to_string_lossy().into_owned());
+
+    let mut failures = 0;
+    for algorithm in args.selected_algorithms() {
+        let algo_id = algorithm.name();
+        let mut instance = algorithms.build::<W>(algorithm, false);
+        let times = match measure_algo_stats(args, instance.as_mut(), bench) {
+            Ok(times) => times,
+            Err(error) => {
+                println!("{: <30} failed, {}", algo_id, error);
+                failures += 1;
+                continue;
+            }
+        };
+        let summary = summarize(&times);
+        println!(
+            "{: <30} {:.03} +/- {:.03} ms, median {:.03} ms, 95% CI [{:.03}, {:.03}] ms, {} runs",
+            algo_id,
+            summary.mean,
+            summary.std,
+            summary.median,
+            summary.ci_low,
+            summary.ci_high,
+            times.len()
+        );
+
+        let Some(output) = &bench.output else {
+            continue;
+        };
+        let record = BenchRecord {
+            algorithm: algo_id.to_string(),
+            threads: algorithm.uses_threads().then_some(args.threads),
+            block_size: algorithm.uses_block_size().then_some(args.block_size),
+            blocks: algorithm.uses_blocks().then_some(args.blocks),
+            graph: graph_name.clone(),
+            nodes: file.graph.node_count,
+            edges: file.edge_count,
+            weight: args.weight.name().to_string(),
+            warmup: bench.warmup,
+            iterations: times.len(),
+            min: summary.min,
+            p5: summary.p5,
+            median: summary.median,
+            p95: summary.p95,
+            max: summary.max,
+            mean: summary.mean,
+            std: summary.std,
+            ci_low: summary.ci_low,
+            ci_high: summary.ci_high,
+            outliers: summary.outliers,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |duration| duration.as_secs()),
+        };
+        let format = output_format(output, bench.output_format);
+        if let Err(error) = append_record(output, format, &record) {
+            eprintln!("Error writing to {}: {}", output, error);
+        }
+    }
+    failures
 }
@@ -0,0 +1,145 @@
+// Dense matrix which stores all rows after each other in one vector
+// The algorithms use it for the distance and predecessor matrices, such that a row is one
+// contiguous slice and the whole matrix is a single allocation
+// 30-01-2024
+
+// Import crates
+use std::ops::{Index, IndexMut};
+use std::slice::{ChunksExact, ChunksExactMut};
+
+/// Row-major matrix, the entry (i, j) is stored at `i * cols + j`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Matrix<T> {
+    rows: usize,
+    cols: usize,
+    data: Vec<T>,
+}
+
+impl<T> Matrix<T> {
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// All entries, row after row
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.data
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.data[row * self.cols..(row + 1) * self.cols]
+    }
+
+    pub fn row_mut(&mut self, row: usize) -> &mut [T] {
+        &mut self.data[row * self.cols..(row + 1) * self.cols]
+    }
+
+    /// Iterate over the rows as slices
+    pub fn iter_rows(&self) -> ChunksExact<'_, T> {
+        // chunks_exact does not accept a size of zero, a matrix without columns has no data
+        self.data.chunks_exact(self.cols.max(1))
+    }
+
+    pub fn iter_rows_mut(&mut self) -> ChunksExactMut<'_, T> {
+        self.data.chunks_exact_mut(self.cols.max(1))
+    }
+
+    /// Apply f to every entry
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Matrix<U> {
+        Matrix {
+            rows: self.rows,
+            cols: self.cols,
+            data: self.data.iter().map(f).collect(),
+        }
+    }
+}
+
+impl<T: Clone> Matrix<T> {
+    /// Matrix of size (rows x cols) with every entry set to value
+    pub fn new(rows: usize, cols: usize, value: T) -> Self {
+        Matrix {
+            rows,
+            cols,
+            data: vec![value; rows * cols],
+        }
+    }
+
+    /// Build the matrix from nested rows, which must all have the same length
+    pub fn from_rows(rows: &[Vec<T>]) -> Self {
+        let cols = rows.first().map_or(0, |row| row.len());
+        let mut data = Vec::with_capacity(rows.len() * cols);
+        for row in rows {
+            assert_eq!(row.len(), cols, "all rows of a matrix must have the same length");
+            data.extend_from_slice(row);
+        }
+
+        Matrix {
+            rows: rows.len(),
+            cols,
+            data,
+        }
+    }
+
+    /// Copy the block of (rows x cols) starting at (row_start, col_start). The block is cut off
+    /// at the border of the matrix, so the blocks at the end can be smaller
+    pub fn block(&self, row_start: usize, col_start: usize, rows: usize, cols: usize) -> Matrix<T> {
+        let row_end = (row_start + rows).min(self.rows);
+        let col_end = (col_start + cols).min(self.cols);
+        let mut data = Vec::with_capacity((row_end - row_start) * (col_end - col_start));
+        for row in row_start..row_end {
+            data.extend_from_slice(&self.row(row)[col_start..col_end]);
+        }
+
+        Matrix {
+            rows: row_end - row_start,
+            cols: col_end - col_start,
+            data,
+        }
+    }
+
+    /// Copy a block back into the matrix with its first entry at (row_start, col_start)
+    pub fn write_block(&mut self, row_start: usize, col_start: usize, block: &Matrix<T>) {
+        for (i, row) in block.iter_rows().enumerate().take(block.rows) {
+            self.row_mut(row_start + i)[col_start..col_start + block.cols].clone_from_slice(row);
+        }
+    }
+
+    /// Convert to nested rows
+    pub fn to_rows(&self) -> Vec<Vec<T>> {
+        self.iter_rows().take(self.rows).map(|row| row.to_vec()).collect()
+    }
+}
+
+// An empty matrix of size (0 x 0), also for entry types without a default value
+impl<T> Default for Matrix<T> {
+    fn default() -> Self {
+        Matrix {
+            rows: 0,
+            cols: 0,
+            data: Vec::new(),
+        }
+    }
+}
+
+impl<T> Index<(usize, usize)> for Matrix<T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, (row, col): (usize, usize)) -> &T {
+        &self.data[row * self.cols + col]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Matrix<T> {
+    #[inline]
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut T {
+        &mut self.data[row * self.cols + col]
+    }
+}
@@ -0,0 +1,37 @@
+// Thread pool shared by the rayon based algorithms
+// All rayon algorithms run on the pool they are given, so one pool controls how many cores they
+// use and the algorithms can be compared on exactly the same threads
+// 30-01-2024
+
+// Import crates
+use rayon::{ThreadPool, ThreadPoolBuildError, ThreadPoolBuilder};
+use std::sync::Arc;
+
+/// Handle to a rayon thread pool, cloning it gives another handle to the same threads
+#[derive(Clone, Debug)]
+pub struct Pool {
+    pool: Arc<ThreadPool>,
+}
+
+impl Pool {
+    /// Start a pool with num_threads threads, zero means one thread per core
+    pub fn new(num_threads: usize) -> Result<Self, ThreadPoolBuildError> {
+        let pool = ThreadPoolBuilder::new().num_threads(num_threads).build()?;
+        Ok(Pool {
+            pool: Arc::new(pool),
+        })
+    }
+
+    pub fn num_threads(&self) -> usize {
+        self.pool.current_num_threads()
+    }
+
+    /// Run op on the pool, the rayon parallel iterators used inside op run on its threads
+    pub fn install<R, F>(&self, op: F) -> R
+    where
+        R: Send,
+        F: FnOnce() -> R + Send,
+    {
+        self.pool.install(op)
+    }
+}
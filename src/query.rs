@@ -0,0 +1,246 @@
+// Shortest paths from a chosen set of source nodes instead of all pairs
+// A query with k sources runs Dijkstra k times and returns a k-by-n table (or k-by-t if only t
+// targets are asked for), so the n^2 entries of the full APSP are never computed
+// 30-01-2024
+
+// Import crates from our files
+use crate::algorithms::dijkstra_csr::dijkstra_search;
+use crate::base::{path_from_row, DistanceMatrix, ExecuteError, PredecessorMatrix};
+use crate::graph::GraphCSR;
+use crate::matrix::Matrix;
+use crate::weight::Weight;
+
+/// The sources (and optionally the targets) of a shortest path query
+#[derive(Clone, Debug)]
+pub struct Query {
+    pub sources: Vec<usize>,
+    /// None means all nodes
+    pub targets: Option<Vec<usize>>,
+    pub track_paths: bool,
+}
+
+impl Query {
+    /// Query the distances from the given sources to all nodes
+    pub fn new(sources: &[usize]) -> Self {
+        Query {
+            sources: sources.to_vec(),
+            targets: None,
+            track_paths: false,
+        }
+    }
+
+    /// Query the distances from one source to all nodes
+    pub fn single_source(source: usize) -> Self {
+        Self::new(&[source])
+    }
+
+    /// Only compute the distances to the given targets. The search from a source stops as soon
+    /// as the distances to all targets are known
+    pub fn with_targets(mut self, targets: &[usize]) -> Self {
+        self.targets = Some(targets.to_vec());
+        self
+    }
+
+    /// Also compute the predecessors such that the paths can be reconstructed
+    pub fn with_paths(mut self) -> Self {
+        self.track_paths = true;
+        self
+    }
+}
+
+/// The answer to a [`Query`], with one row per source and one column per target
+#[derive(Clone, Debug)]
+pub struct DistanceTable<W> {
+    pub sources: Vec<usize>,
+    /// The targets of the query, all nodes if the query did not give any
+    pub targets: Vec<usize>,
+    /// `distances[(r, c)]` is the distance from `sources[r]` to `targets[c]`, INFINITY if there
+    /// is no path
+    pub distances: DistanceMatrix<W>,
+    /// Row r holds the predecessors on the paths from `sources[r]` to all nodes. Only filled if
+    /// the query asked for the paths
+    pub predecessors: Option<PredecessorMatrix>,
+}
+
+impl<W: Weight> DistanceTable<W> {
+    /// The distance from source to target, None if there is no path or if the nodes were not
+    /// part of the query
+    pub fn distance(&self, source: usize, target: usize) -> Option<W> {
+        let row = self.sources.iter().position(|&node| node == source)?;
+        let col = self.targets.iter().position(|&node| node == target)?;
+        self.distances[(row, col)].finite()
+    }
+
+    /// The distances from `sources[row]` to all targets
+    pub fn row(&self, row: usize) -> &[W] {
+        self.distances.row(row)
+    }
+
+    /// Return the sequence of nodes on the shortest path from source to target (both included).
+    /// Returns None if there is no path, if the paths were not tracked or if the nodes were not
+    /// part of the query
+    pub fn path(&self, source: usize, target: usize) -> Option<Vec<usize>> {
+        let predecessors = self.predecessors.as_ref()?;
+        let row = self.sources.iter().position(|&node| node == source)?;
+        self.targets.iter().position(|&node| node == target)?;
+        path_from_row(predecessors.row(row), source, target)
+    }
+}
+
+/// Algorithms which can answer a [`Query`] without computing all pairs
+pub trait ShortestPathQuery<W: Weight> {
+    /// Compute the distances for the query on the loaded graph
+    fn query(&self, query: &Query) -> Result<DistanceTable<W>, ExecuteError>;
+}
+
+// Check that a node of the query is in the graph
+fn check_node(node: usize, node_count: usize) -> Result<(), ExecuteError> {
+    if node < node_count {
+        Ok(())
+    } else {
+        Err(ExecuteError::InvalidNode { node, node_count })
+    }
+}
+
+/// Answer a query with Dijkstra, one search per source
+pub fn dijkstra_query<W: Weight>(
+    graph: &GraphCSR<W>,
+    query: &Query,
+) -> Result<DistanceTable<W>, ExecuteError> {
+    let n = graph.node_count;
+
+    // Dijkstra gives wrong answers for negative weights, so refuse those graphs
+    if let Some((from, to)) = graph.negative_edge() {
+        return Err(ExecuteError::NegativeEdge { from, to });
+    }
+    for &node in query.sources.iter().chain(query.targets.iter().flatten()) {
+        check_node(node, n)?;
+    }
+
+    // Mark the targets, such that the search can stop once all of them are settled
+    let targets: Vec<usize> = match &query.targets {
+        Some(targets) => targets.clone(),
+        None => (0..n).collect(),
+    };
+    let mut is_target = vec![false; n];
+    let mut target_count = 0;
+    if query.targets.is_some() {
+        for &target in &targets {
+            if !is_target[target] {
+                is_target[target] = true;
+                target_count += 1;
+            }
+        }
+    }
+
+    let k = query.sources.len();
+    let mut distances = Matrix::new(k, targets.len(), W::INFINITY);
+    let mut predecessors = if query.track_paths {
+        Matrix::new(k, n, None)
+    } else {
+        Matrix::default()
+    };
+
+    // Scratch rows, the distances are needed for all nodes during the search
+    let mut distance = vec![W::INFINITY; n];
+    let mut scratch: Vec<Option<usize>> = Vec::new();
+
+    for (r, &source) in query.sources.iter().enumerate() {
+        distance.fill(W::INFINITY);
+        let predecessor_row = if query.track_paths {
+            predecessors.row_mut(r)
+        } else {
+            scratch.clear();
+            scratch.resize(n, None);
+            &mut scratch[..]
+        };
+
+        // Without targets the search runs until all reachable nodes are settled
+        let mut remaining = target_count;
+        dijkstra_search(graph, source, &mut distance, predecessor_row, |node| {
+            if is_target[node] {
+                remaining -= 1;
+                remaining == 0
+            } else {
+                false
+            }
+        });
+
+        for (value, &target) in distances.row_mut(r).iter_mut().zip(&targets) {
+            *value = distance[target];
+        }
+    }
+
+    Ok(DistanceTable {
+        sources: query.sources.clone(),
+        targets,
+        distances,
+        predecessors: if query.track_paths {
+            Some(predecessors)
+        } else {
+            None
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::dijkstra_csr::dijkstra_csr;
+    use crate::generate::{GraphGenerator, Model};
+
+    // The weight of the lightest edge from -> to
+    fn edge_weight(graph: &GraphCSR<u32>, from: usize, to: usize) -> Option<u32> {
+        graph.neighbours(from).filter(|&(node, _)| node == to).map(|(_, weight)| weight).min()
+    }
+
+    #[test]
+    fn query_matches_all_pairs() {
+        let graph: GraphCSR<u32> = GraphGenerator::new(Model::Gnp { nodes: 40, probability: 0.08 })
+            .directed(true)
+            .with_seed(3)
+            .build()
+            .unwrap();
+        let (all_pairs, _) = dijkstra_csr(&graph, false);
+
+        // Source 3 is asked twice and is also a target, target 5 is asked twice
+        let sources = [3, 0, 3];
+        let targets = [5, 3, 5, 0, 39];
+        let query = Query::new(&sources).with_targets(&targets).with_paths();
+        let table = dijkstra_query(&graph, &query).unwrap();
+        assert_eq!(table.distances.rows(), sources.len());
+        assert_eq!(table.distances.cols(), targets.len());
+        for (r, &source) in sources.iter().enumerate() {
+            for (c, &target) in targets.iter().enumerate() {
+                assert_eq!(table.distances[(r, c)], all_pairs[(source, target)]);
+                let Some(distance) = table.distance(source, target) else {
+                    assert_eq!(table.path(source, target), None);
+                    continue;
+                };
+                let path = table.path(source, target).unwrap();
+                assert_eq!((path[0], path[path.len() - 1]), (source, target));
+                let weight: u32 = path
+                    .windows(2)
+                    .map(|edge| edge_weight(&graph, edge[0], edge[1]).unwrap())
+                    .sum();
+                assert_eq!(weight, distance);
+            }
+        }
+        assert_eq!(table.distance(3, 3), Some(0));
+        // Node 1 is in the graph, but not part of the query
+        assert_eq!(table.distance(1, 5), None);
+        assert_eq!(table.distance(0, 1), None);
+
+        // Without targets the rows are the rows of all pairs
+        let table = dijkstra_query(&graph, &Query::new(&sources)).unwrap();
+        for (r, &source) in sources.iter().enumerate() {
+            assert_eq!(table.row(r), all_pairs.row(source));
+        }
+        assert!(table.predecessors.is_none());
+
+        assert_eq!(
+            dijkstra_query(&graph, &Query::single_source(0).with_targets(&[40])).unwrap_err(),
+            ExecuteError::InvalidNode { node: 40, node_count: 40 }
+        );
+    }
+}
@@ -0,0 +1,112 @@
+// The benchmark results written by `apsp bench --output`
+// Every measurement is one self-describing record, such that the results can be read back without
+// knowing in which order the algorithms ran
+// 30-01-2024
+
+// Import crates
+use serde::Serialize;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::Path;
+
+// Import crates from the library
+use apsp::export::csv_field;
+
+// Import crates from our files
+use crate::cli::OutputFormat;
+
+// One measured algorithm on one graph. The times are in milliseconds, the parameters which do not
+// apply to the algorithm are left empty. `iterations` counts the measured executions after the
+// `warmup` ones, ci_low and ci_high bound the 95% confidence interval of the mean
+#[derive(Serialize, Debug)]
+pub struct BenchRecord {
+    pub algorithm: String,
+    pub threads: Option<usize>,
+    pub block_size: Option<usize>,
+    pub blocks: Option<usize>,
+    pub graph: String,
+    pub nodes: usize,
+    pub edges: usize,
+    pub weight: String,
+    pub warmup: usize,
+    pub iterations: usize,
+    pub min: f64,
+    pub p5: f64,
+    pub median: f64,
+    pub p95: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub std: f64,
+    pub ci_low: f64,
+    pub ci_high: f64,
+    // The number of times outside 1.5 times the interquartile range
+    pub outliers: usize,
+    // Seconds since the Unix epoch
+    pub timestamp: u64,
+}
+
+// The columns of the CSV files, in the order of the fields
+const CSV_HEADER: &str = "algorithm,threads,block_size,blocks,graph,nodes,edges,weight,warmup,\
+                          iterations,min,p5,median,p95,max,mean,std,ci_low,ci_high,outliers,\
+                          timestamp";
+
+fn csv_optional(value: Option<usize>) -> String {
+    value.map_or(String::new(), |value| value.to_string())
+}
+
+impl BenchRecord {
+    fn to_csv(&self) -> String {
+        [
+            csv_field(&self.algorithm),
+            csv_optional(self.threads),
+            csv_optional(self.block_size),
+            csv_optional(self.blocks),
+            csv_field(&self.graph),
+            self.nodes.to_string(),
+            self.edges.to_string(),
+            csv_field(&self.weight),
+            self.warmup.to_string(),
+            self.iterations.to_string(),
+            self.min.to_string(),
+            self.p5.to_string(),
+            self.median.to_string(),
+            self.p95.to_string(),
+            self.max.to_string(),
+            self.mean.to_string(),
+            self.std.to_string(),
+            self.ci_low.to_string(),
+            self.ci_high.to_string(),
+            self.outliers.to_string(),
+            self.timestamp.to_string(),
+        ]
+        .join(",")
+    }
+}
+
+// The format of the output file, JSON for a .json or .jsonl file and CSV otherwise
+pub fn output_format(path: &str, format: Option<OutputFormat>) -> OutputFormat {
+    format.unwrap_or_else(|| {
+        match Path::new(path).extension().and_then(|extension| extension.to_str()) {
+            Some("json") | Some("jsonl") => OutputFormat::Json,
+            _ => OutputFormat::Csv,
+        }
+    })
+}
+
+// Append the record to the file. A new CSV file starts with the header, the JSON records are
+// written one per line, such that a file can be appended by several runs
+pub fn append_record(path: &str, format: OutputFormat, record: &BenchRecord) -> io::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    match format {
+        OutputFormat::Csv => {
+            if file.metadata()?.len() == 0 {
+                writeln!(file, "{}", CSV_HEADER)?;
+            }
+            writeln!(file, "{}", record.to_csv())
+        }
+        OutputFormat::Json => {
+            let line = serde_json::to_string(record)?;
+            writeln!(file, "{}", line)
+        }
+    }
+}
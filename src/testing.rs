@@ -0,0 +1,36 @@
+// Helpers shared by the unit tests
+// 30-01-2024
+
+// Import crates
+use std::fs;
+
+/// A file in the temporary directory for a test, which is removed again when it is dropped, also
+/// when the test fails. The name is prefixed with the process id, such that two test runs at the
+/// same time do not use the same file
+pub(crate) struct TempFile {
+    pub path: String,
+}
+
+impl TempFile {
+    /// The path of a file that is not created yet, e.g. for a writer
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("apsp_{}_{}", std::process::id(), name));
+        TempFile {
+            path: path.to_str().expect("temporary path is not UTF-8").to_string(),
+        }
+    }
+
+    /// A file with the contents, e.g. a graph for a loader
+    pub fn with_contents(name: &str, contents: &str) -> Self {
+        let file = Self::new(name);
+        fs::write(&file.path, contents).expect("can't write the temporary file");
+        file
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        // The file does not exist if the test expected the writer to fail
+        let _ = fs::remove_file(&self.path);
+    }
+}
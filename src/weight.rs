@@ -0,0 +1,142 @@
+// Trait for the edge weights used by the algorithms
+// Pairs without a path get the distance INFINITY instead of None, such that the distance
+// matrices only store the weights and the inner loops don't have to branch on an Option
+// 30-01-2024
+
+// Import crates
+use num::{Num, NumCast};
+use std::cmp::PartialOrd;
+use std::fmt::Debug;
+
+/// A weight type with a sentinel value for "no path"
+pub trait Weight: Num + NumCast + Copy + PartialOrd + Debug {
+    /// The distance of a pair without a path, larger than every real distance
+    const INFINITY: Self;
+    /// The largest real distance. The values between it and INFINITY are only the result of a
+    /// clamped sum, so a distance of exactly MAX_FINITE is not mistaken for an overflow
+    const MAX_FINITE: Self;
+    /// The smallest real distance, the values below it are only the result of a clamped sum
+    const MIN_FINITE: Self;
+    /// The value of a sum above MAX_FINITE, see [`Weight::add_weight`]
+    const SATURATED: Self;
+
+    /// Add two distances, the sum is INFINITY if one of them is INFINITY. A sum that does not fit
+    /// in the range of the real distances is clamped (to SATURATED or the minimum of the type)
+    /// instead of wrapping around or panicking, and stays there when more is added
+    fn add_weight(self, other: Self) -> Self;
+
+    /// True if the value is the result of a clamped sum, see [`Weight::add_weight`]
+    #[inline]
+    fn is_saturated(self) -> bool {
+        !self.is_infinite() && (self > Self::MAX_FINITE || self < Self::MIN_FINITE)
+    }
+
+    #[inline]
+    fn is_infinite(self) -> bool {
+        self == Self::INFINITY
+    }
+
+    /// None for INFINITY, the distance otherwise
+    #[inline]
+    fn finite(self) -> Option<Self> {
+        if self.is_infinite() {
+            None
+        } else {
+            Some(self)
+        }
+    }
+}
+
+// The largest value of an integer type is used as INFINITY and the one below as SATURATED, such
+// that a sum of finite values can be clamped without becoming INFINITY. The minimum of an
+// unsigned type is zero, which is a normal distance. For the signed types the minimum is kept for
+// a clamped negative sum, which saturating_add already does
+macro_rules! impl_weight_int {
+    (unsigned: $($t: ty),*) => {
+        $(impl_weight_int!(@impl $t, 0);)*
+    };
+    (signed: $($t: ty),*) => {
+        $(impl_weight_int!(@impl $t, <$t>::MIN + 1);)*
+    };
+    (@impl $t: ty, $min_finite: expr) => {
+        impl Weight for $t {
+            const INFINITY: Self = <$t>::MAX;
+            const MAX_FINITE: Self = <$t>::MAX - 2;
+            const MIN_FINITE: Self = $min_finite;
+            const SATURATED: Self = <$t>::MAX - 1;
+
+            #[inline]
+            fn add_weight(self, other: Self) -> Self {
+                if self == Self::INFINITY || other == Self::INFINITY {
+                    return Self::INFINITY;
+                }
+                let sum = self.saturating_add(other);
+                if sum > Self::MAX_FINITE {
+                    Self::SATURATED
+                } else {
+                    sum
+                }
+            }
+        }
+    };
+}
+
+// The floating point types have their own infinity, so SATURATED is the largest float and
+// MAX_FINITE the one below it. A sum of finite values which overflows to an infinity is clamped
+// like for the integer types
+macro_rules! impl_weight_float {
+    ($($t: ty),*) => {
+        $(
+            impl Weight for $t {
+                const INFINITY: Self = <$t>::INFINITY;
+                const MAX_FINITE: Self = <$t>::from_bits(<$t>::MAX.to_bits() - 1);
+                const MIN_FINITE: Self = -Self::MAX_FINITE;
+                const SATURATED: Self = <$t>::MAX;
+
+                #[inline]
+                fn add_weight(self, other: Self) -> Self {
+                    if self == Self::INFINITY || other == Self::INFINITY {
+                        return Self::INFINITY;
+                    }
+                    let sum = self + other;
+                    if sum > Self::MAX_FINITE {
+                        Self::SATURATED
+                    } else if sum < Self::MIN_FINITE {
+                        <$t>::MIN
+                    } else {
+                        sum
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_weight_int!(unsigned: u8, u16, u32, u64, u128, usize);
+impl_weight_int!(signed: i8, i16, i32, i64, i128, isize);
+impl_weight_float!(f32, f64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_clamped_sums_are_saturated() {
+        // A sum of exactly MAX_FINITE is a real distance
+        assert_eq!(32766u16.add_weight(32767), u16::MAX_FINITE);
+        assert!(!u16::MAX_FINITE.is_saturated());
+        assert_eq!(32767u16.add_weight(32767), u16::SATURATED);
+        assert!(u16::SATURATED.is_saturated());
+        // A clamped sum stays clamped
+        assert_eq!(u16::SATURATED.add_weight(0), u16::SATURATED);
+        assert!(!u16::INFINITY.is_saturated());
+
+        assert!(!(-100i8).add_weight(-27).is_saturated());
+        assert!((-100i8).add_weight(-28).is_saturated());
+
+        assert!(!f64::MAX_FINITE.is_saturated());
+        assert_eq!(f64::MAX_FINITE.add_weight(f64::MAX_FINITE), f64::SATURATED);
+        assert_eq!((-f64::MAX_FINITE).add_weight(-1e300), f64::MIN);
+        assert!(f64::MIN.is_saturated());
+    }
+}